@@ -0,0 +1,142 @@
+use crate::{spell_sprite, DamageEvent, SpellsSheetSmall};
+use ar_core::{
+    BattleSet, DamageType, Layer, MonsterMarker, OnHitEffect, PlayerMarker, PlayerSpellMarker,
+    PlayerStats, Stat,
+};
+use ar_spells::generator::OwnedAuraSpells;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy::utils::Duration;
+
+/// The auras are see-through so the monsters inside stay visible
+const AURA_ALPHA: f32 = 0.35;
+
+pub struct AuraPlugin;
+
+impl Plugin for AuraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (sync_player_auras, scale_auras, aura_damage)
+                .chain()
+                .in_set(BattleSet),
+        );
+    }
+}
+
+/// An aura around the player, it is a child of the player like the magnet
+#[derive(Component)]
+struct Aura {
+    spell: String,
+    damage: usize,
+    base_radius: f32,
+    radius: f32,
+    tick: Timer,
+}
+
+/// Spawns an aura for each aura spell the player owns, keeps the existing ones up to date
+/// with their spell, and despawns the ones of the spells it doesn't own anymore
+fn sync_player_auras(
+    mut commands: Commands,
+    player: Query<(Entity, &OwnedAuraSpells), With<PlayerMarker>>,
+    mut auras: Query<(Entity, &mut Aura)>,
+    sprite_sheet: Res<SpellsSheetSmall>,
+) {
+    let Ok((player_id, owned_auras)) = player.get_single() else {
+        return;
+    };
+    for (aura_id, mut aura) in auras.iter_mut() {
+        let Some(spell) = owned_auras.spells.iter().find(|s| s.name == aura.spell) else {
+            commands.entity(aura_id).despawn_recursive();
+            continue;
+        };
+        // the spell may have been leveled up
+        aura.damage = spell.damage;
+        aura.base_radius = spell.radius;
+        let tick_rate = Duration::from_secs_f32(spell.tick_rate);
+        if aura.tick.duration() != tick_rate {
+            aura.tick.set_duration(tick_rate);
+        }
+    }
+    for spell in owned_auras.spells.iter() {
+        if auras.iter().any(|(_, aura)| aura.spell == spell.name) {
+            continue;
+        }
+        // the size is set by scale_auras
+        let mut sprite = spell_sprite(
+            sprite_sheet.sprite.get(spell.sprite.as_str()),
+            Vec2::ZERO,
+            Color::srgb_u8(155, 188, 15),
+        );
+        sprite.color.set_alpha(AURA_ALPHA);
+        let aura_id = commands
+            .spawn(sprite)
+            .insert(Transform::from_xyz(0.0, 0.0, -1.0))
+            .insert(Aura {
+                spell: spell.name.clone(),
+                damage: spell.damage,
+                base_radius: spell.radius,
+                radius: 0.0,
+                tick: Timer::from_seconds(spell.tick_rate, TimerMode::Repeating),
+            })
+            .insert(PlayerSpellMarker)
+            .insert(spell.damage_type)
+            .insert(Collider::circle(spell.radius))
+            .insert(Sensor)
+            .insert(CollisionLayers::new([Layer::PlayerAOE], [Layer::Monster]))
+            .insert(CollidingEntities::default())
+            .id();
+        if let Some(effect) = &spell.effect {
+            commands.entity(aura_id).insert(OnHitEffect(effect.clone()));
+        }
+        commands.entity(player_id).add_child(aura_id);
+    }
+}
+
+/// Resizes the auras with the player's area stat
+fn scale_auras(
+    mut commands: Commands,
+    player: Query<&PlayerStats, With<PlayerMarker>>,
+    mut auras: Query<(Entity, &mut Aura, &mut Sprite)>,
+) {
+    let Ok(stats) = player.get_single() else {
+        return;
+    };
+    let area = stats.get(Stat::Area);
+    for (aura_id, mut aura, mut sprite) in auras.iter_mut() {
+        let radius = aura.base_radius * area;
+        if aura.radius == radius {
+            continue;
+        }
+        aura.radius = radius;
+        sprite.custom_size = Some(Vec2::splat(radius * 2.0));
+        commands.entity(aura_id).insert(Collider::circle(radius));
+    }
+}
+
+/// Damages every monster inside the auras each time their tick finishes,
+/// the tick is scaled by the player's cooldown stat
+fn aura_damage(
+    time: Res<Time>,
+    player: Query<&PlayerStats, With<PlayerMarker>>,
+    mut auras: Query<(Entity, &mut Aura, &CollidingEntities, &DamageType)>,
+    monsters: Query<(), With<MonsterMarker>>,
+    mut ev_damage: EventWriter<DamageEvent>,
+) {
+    let cooldown = player.get_single().map_or(1.0, |stats| stats.cooldown());
+    let delta = time.delta().div_f32(cooldown);
+    for (aura_id, mut aura, colliding, damage_type) in auras.iter_mut() {
+        if !aura.tick.tick(delta).just_finished() {
+            continue;
+        }
+        for monster in colliding.iter().filter(|e| monsters.contains(**e)) {
+            ev_damage.send(DamageEvent {
+                damage: aura.damage,
+                target: *monster,
+                source: aura_id,
+                on_hit: true,
+                damage_type: *damage_type,
+            });
+        }
+    }
+}
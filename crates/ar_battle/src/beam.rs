@@ -0,0 +1,130 @@
+use crate::{spell_sprite, DamageEvent, SpellsSheetSmall};
+use ar_core::{
+    BattleSet, DamageType, Layer, MonsterMarker, PlayerLastDirection, PlayerMarker,
+    PlayerSpellMarker, PlayerStats, Stat,
+};
+use ar_spells::generator::OwnedBeamSpells;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+pub struct BeamPlugin;
+
+impl Plugin for BeamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (cast_player_beams, channel_beams).chain().in_set(BattleSet),
+        );
+    }
+}
+
+/// A beam being channeled by the player, it has no collider,
+/// its hits are found with a ray cast each tick
+#[derive(Component)]
+struct Beam {
+    damage: usize,
+    length: f32,
+    width: f32,
+    pierce: bool,
+    channel: Timer,
+    tick: Timer,
+}
+
+/// Starts channeling the player's beam spells when their cooldown finishes
+fn cast_player_beams(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player: Query<(&mut OwnedBeamSpells, &PlayerStats), With<PlayerMarker>>,
+    sprite_sheet: Res<SpellsSheetSmall>,
+) {
+    let Ok((mut beams, stats)) = player.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta().div_f32(stats.cooldown());
+    let area = stats.get(Stat::Area);
+    let duration_multiplier = stats.get(Stat::Duration);
+    for beam in beams.spells.iter_mut() {
+        if !beam.cooldown.tick(delta).just_finished() {
+            continue;
+        }
+        // the size of the sprite is set while channeling
+        let sprite = spell_sprite(
+            sprite_sheet.sprite.get(beam.sprite.as_str()),
+            Vec2::ZERO,
+            Color::srgba_u8(255, 120, 80, 200),
+        );
+        commands
+            .spawn(sprite)
+            .insert(Transform::default())
+            .insert(Beam {
+                damage: beam.damage,
+                length: beam.length * area,
+                width: beam.width * area,
+                pierce: beam.pierce,
+                channel: Timer::from_seconds(beam.duration * duration_multiplier, TimerMode::Once),
+                tick: Timer::from_seconds(beam.tick_rate, TimerMode::Repeating),
+            })
+            .insert(PlayerSpellMarker)
+            .insert(beam.damage_type);
+    }
+}
+
+/// Casts each beam from the player along the aim direction, up to the first wall,
+/// and damages the first monster on its way or every one of them if it pierces
+#[allow(clippy::type_complexity)]
+fn channel_beams(
+    mut commands: Commands,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    player_last_direction: Res<PlayerLastDirection>,
+    player: Query<&Transform, (With<PlayerMarker>, Without<Beam>)>,
+    mut beams: Query<(Entity, &mut Beam, &mut Sprite, &mut Transform, &DamageType)>,
+    monsters: Query<(), With<MonsterMarker>>,
+    mut ev_damage: EventWriter<DamageEvent>,
+) {
+    let Ok(player_transform) = player.get_single() else {
+        return;
+    };
+    let origin = player_transform.translation.truncate();
+    let Ok(direction) = Dir2::new(player_last_direction.direction) else {
+        return;
+    };
+    let wall_filter = SpatialQueryFilter::from_mask(Layer::Wall);
+    let monster_filter = SpatialQueryFilter::from_mask(Layer::Monster);
+
+    for (beam_id, mut beam, mut sprite, mut transform, damage_type) in beams.iter_mut() {
+        if beam.channel.tick(time.delta()).finished() {
+            commands.entity(beam_id).despawn_recursive();
+            continue;
+        }
+
+        let length = spatial_query
+            .cast_ray(origin, direction, beam.length, true, &wall_filter)
+            .map_or(beam.length, |hit| hit.distance);
+
+        if beam.tick.tick(time.delta()).just_finished() {
+            let hits = if beam.pierce {
+                spatial_query.ray_hits(origin, direction, length, u32::MAX, true, &monster_filter)
+            } else {
+                spatial_query
+                    .cast_ray(origin, direction, length, true, &monster_filter)
+                    .into_iter()
+                    .collect()
+            };
+            for hit in hits.iter().filter(|hit| monsters.contains(hit.entity)) {
+                ev_damage.send(DamageEvent {
+                    damage: beam.damage,
+                    target: hit.entity,
+                    source: beam_id,
+                    on_hit: true,
+                    damage_type: *damage_type,
+                });
+            }
+        }
+
+        // the sprite is stretched from the player to the end of the beam
+        sprite.custom_size = Some(Vec2::new(length, beam.width));
+        transform.translation = (origin + *direction * length / 2.0).extend(4.0);
+        transform.rotation = Quat::from_rotation_z(direction.to_angle());
+    }
+}
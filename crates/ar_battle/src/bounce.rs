@@ -0,0 +1,110 @@
+use crate::{despawn_event_handler, handle_collision, DespawnEvent};
+use ar_core::{AppState, Bounce, CollidedHash, PlayerProjectileMarker, WallMarker};
+use avian2d::{prelude::*, schedule::PhysicsSchedule, schedule::PhysicsStepSet};
+use bevy::prelude::*;
+
+/// The range in which a ricochet looks for a new target
+const RICOCHET_RANGE: f32 = 160.0;
+
+pub struct BouncePlugin;
+
+impl Plugin for BouncePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PhysicsSchedule,
+            bounce_off_walls
+                .after(handle_collision)
+                .before(despawn_event_handler)
+                .before(PhysicsStepSet::First)
+                .run_if(in_state(AppState::InBattle)),
+        );
+    }
+}
+
+/// Reflects the player's projectiles off the walls while they have bounces left,
+/// otherwise they are despawned,
+/// after a bounce the projectile can hit the same monsters again
+#[allow(clippy::type_complexity)]
+fn bounce_off_walls(
+    mut ev_collision_reader: EventReader<CollisionStarted>,
+    mut despawn_event: EventWriter<DespawnEvent>,
+    walls: Query<&ColliderAabb, With<WallMarker>>,
+    mut projectiles: Query<
+        (
+            &Position,
+            &mut LinearVelocity,
+            Option<&mut Bounce>,
+            &mut CollidedHash,
+        ),
+        With<PlayerProjectileMarker>,
+    >,
+) {
+    for CollisionStarted(entity1, entity2) in ev_collision_reader.read() {
+        let (wall, projectile) = if walls.contains(*entity1) {
+            (*entity1, *entity2)
+        } else if walls.contains(*entity2) {
+            (*entity2, *entity1)
+        } else {
+            continue;
+        };
+        let (Ok(aabb), Ok((position, mut velocity, bounce, mut collided))) =
+            (walls.get(wall), projectiles.get_mut(projectile))
+        else {
+            continue;
+        };
+        match bounce {
+            Some(mut bounce) if bounce.0 > 0 => {
+                bounce.0 -= 1;
+                let normal = wall_normal(aabb, position.0);
+                let speed_into_wall = velocity.0.dot(normal);
+                if speed_into_wall < 0.0 {
+                    velocity.0 -= 2.0 * speed_into_wall * normal;
+                }
+                collided.0.clear();
+            }
+            _ => {
+                despawn_event.send(DespawnEvent { entity: projectile });
+            }
+        }
+    }
+}
+
+/// The normal of the wall's face closest to the position,
+/// the face with the smallest penetration is the one that was hit
+fn wall_normal(aabb: &ColliderAabb, position: Vec2) -> Vec2 {
+    let center = (aabb.min + aabb.max) / 2.0;
+    let half_size = (aabb.max - aabb.min) / 2.0;
+    let offset = position - center;
+    if half_size.x - offset.x.abs() < half_size.y - offset.y.abs() {
+        Vec2::new(offset.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, offset.y.signum())
+    }
+}
+
+/// The direction from the position towards the nearest monster
+/// that the projectile hasn't hit yet, within the ricochet range
+pub(crate) fn ricochet_direction(
+    position: Vec2,
+    collided: &CollidedHash,
+    monsters: impl Iterator<Item = (Entity, Vec2)>,
+) -> Option<Vec2> {
+    nearest_unhit_direction(position, collided, monsters, RICOCHET_RANGE)
+}
+
+/// The direction from the position towards the nearest monster in range
+/// that the projectile hasn't hit yet
+pub(crate) fn nearest_unhit_direction(
+    position: Vec2,
+    collided: &CollidedHash,
+    monsters: impl Iterator<Item = (Entity, Vec2)>,
+    range: f32,
+) -> Option<Vec2> {
+    monsters
+        .filter(|(entity, _)| !collided.0.contains(entity))
+        .map(|(_, monster)| (monster, monster.distance_squared(position)))
+        .filter(|(_, distance)| *distance <= range * range)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(monster, _)| (monster - position).normalize_or_zero())
+        .filter(|direction| *direction != Vec2::ZERO)
+}
@@ -0,0 +1,55 @@
+use ar_core::{ActiveBuffs, BattleSet, PlayerMarker, PlayerStats, Stat};
+use ar_spells::generator::OwnedBuffSpells;
+use bevy::prelude::*;
+
+pub struct BuffsPlugin;
+
+impl Plugin for BuffsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (cast_player_buffs, tick_active_buffs)
+                .chain()
+                .in_set(BattleSet),
+        );
+    }
+}
+
+/// Casts the player's buff spells when their cooldown finishes,
+/// following the stacking rule declared in the spell's template
+fn cast_player_buffs(
+    time: Res<Time>,
+    mut player: Query<(&mut OwnedBuffSpells, &mut ActiveBuffs, &PlayerStats), With<PlayerMarker>>,
+) {
+    if player.is_empty() {
+        return;
+    }
+    let (mut owned_buffs, mut active_buffs, stats) = player.single_mut();
+    let delta = time.delta().div_f32(stats.cooldown());
+    let duration = stats.get(Stat::Duration);
+    for buff in owned_buffs.spells.iter_mut() {
+        if !buff.cooldown.tick(delta).just_finished() {
+            continue;
+        }
+        active_buffs.apply(
+            &buff.name,
+            &buff.modifiers,
+            Timer::from_seconds(buff.duration * duration, TimerMode::Once),
+            buff.stacking,
+            &buff.sprite,
+        );
+    }
+}
+
+/// Ticks the duration of the active buffs, removing the ones that expired
+fn tick_active_buffs(time: Res<Time>, mut active_buffs: Query<&mut ActiveBuffs>) {
+    for mut buffs in active_buffs.iter_mut() {
+        if buffs.0.is_empty() {
+            continue;
+        }
+        for buff in buffs.0.iter_mut() {
+            buff.timer.tick(time.delta());
+        }
+        buffs.0.retain(|buff| !buff.timer.finished());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{spell_sprite, DamageEvent, SpellsSheetSmall};
+use ar_core::{
+    BattleSet, DamageType, LifeTime, MonsterMarker, PlayerMarker, PlayerSpellMarker, PlayerStats,
+};
+use ar_spells::generator::OwnedChainSpells;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+/// How long the line connecting the hops stays on screen
+const CHAIN_VISUAL_LIFETIME: f32 = 0.15;
+/// The width of the line connecting the hops
+const CHAIN_VISUAL_WIDTH: f32 = 2.0;
+
+pub struct ChainPlugin;
+
+impl Plugin for ChainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, cast_player_chains.in_set(BattleSet));
+    }
+}
+
+/// Casts the player's chain spells when their cooldown finishes,
+/// the chain hits the nearest monster and then hops to the nearest monster it hasn't hit,
+/// losing a fraction of its damage on each hop
+fn cast_player_chains(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player: Query<(&mut OwnedChainSpells, &Transform, &PlayerStats), With<PlayerMarker>>,
+    monsters: Query<(Entity, &Transform), With<MonsterMarker>>,
+    sprite_sheet: Res<SpellsSheetSmall>,
+    mut ev_damage: EventWriter<DamageEvent>,
+) {
+    let Ok((mut chains, player_transform, stats)) = player.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta().div_f32(stats.cooldown());
+    for chain in chains.spells.iter_mut() {
+        if !chain.cooldown.tick(delta).just_finished() {
+            continue;
+        }
+        cast_chain(
+            &mut commands,
+            &monsters,
+            &mut ev_damage,
+            ChainCast {
+                origin: player_transform.translation.truncate(),
+                excluded: None,
+                damage: chain.damage as f32,
+                jumps: chain.jumps,
+                radius: chain.radius,
+                falloff: chain.falloff,
+                damage_type: chain.damage_type,
+                sprite: sprite_sheet.sprite.get(chain.sprite.as_str()).cloned(),
+            },
+        );
+    }
+}
+
+/// A chain starting at `origin`, the `excluded` monster can't be hit by it
+pub(crate) struct ChainCast {
+    pub origin: Vec2,
+    pub excluded: Option<Entity>,
+    pub damage: f32,
+    pub jumps: u8,
+    pub radius: f32,
+    pub falloff: f32,
+    pub damage_type: DamageType,
+    pub sprite: Option<Handle<Image>>,
+}
+
+/// Hops from monster to monster, each segment of the chain is the source of its hop's damage,
+/// returns the segments
+pub(crate) fn cast_chain(
+    commands: &mut Commands,
+    monsters: &Query<(Entity, &Transform), With<MonsterMarker>>,
+    ev_damage: &mut EventWriter<DamageEvent>,
+    cast: ChainCast,
+) -> Vec<Entity> {
+    let mut position = cast.origin;
+    let mut hit: HashSet<Entity> = cast.excluded.into_iter().collect();
+    let mut damage = cast.damage;
+    let mut segments = Vec::new();
+    for _ in 0..=cast.jumps {
+        let Some((target, target_position)) = monsters
+            .iter()
+            .filter(|(entity, _)| !hit.contains(entity))
+            .map(|(entity, t)| (entity, t.translation.truncate()))
+            .filter(|(_, p)| p.distance_squared(position) <= cast.radius * cast.radius)
+            .min_by(|(_, a), (_, b)| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            })
+        else {
+            break;
+        };
+        hit.insert(target);
+
+        let segment = target_position - position;
+        let size = Vec2::new(segment.length(), CHAIN_VISUAL_WIDTH);
+        let sprite = spell_sprite(
+            cast.sprite.as_ref(),
+            size,
+            Color::srgba_u8(200, 230, 255, 220),
+        );
+        let segment_id = commands
+            .spawn(sprite)
+            .insert(Transform {
+                translation: ((position + target_position) / 2.0).extend(4.0),
+                rotation: Quat::from_rotation_z(segment.to_angle()),
+                ..Default::default()
+            })
+            .insert(PlayerSpellMarker)
+            .insert(cast.damage_type)
+            .insert(LifeTime {
+                timer: Timer::from_seconds(CHAIN_VISUAL_LIFETIME, TimerMode::Once),
+            })
+            .id();
+        ev_damage.send(DamageEvent {
+            damage: (damage.round() as usize).max(1),
+            target,
+            source: segment_id,
+            on_hit: true,
+            damage_type: cast.damage_type,
+        });
+        segments.push(segment_id);
+
+        position = target_position;
+        damage *= 1.0 - cast.falloff;
+    }
+    segments
+}
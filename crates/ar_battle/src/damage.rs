@@ -0,0 +1,73 @@
+use crate::{AoEDamageMarker, DamageEvent};
+use ar_core::{
+    Damage, DamageType, PlayerMarker, PlayerProjectileMarker, PlayerSpellMarker, PlayerStats,
+    Resistances, Stat,
+};
+use bevy::prelude::*;
+use bevy_rand::prelude::WyRand;
+use bevy_rand::resource::GlobalEntropy;
+use rand_core::RngCore;
+
+/// The damage of a hit after the attacker's stats and the target's resistances
+/// were applied, armor and shield are applied afterwards by damage_applier
+#[derive(Event)]
+pub struct CalculatedDamageEvent {
+    pub damage: usize,
+    pub target: Entity,
+    pub source: Entity,
+    pub on_hit: bool,
+    pub crit: bool,
+    pub damage_type: DamageType,
+}
+
+/// Calculates the damage of each hit, in order:
+/// the player's flat damage and might, the critical hit, then the target's resistance
+/// It must be run between handle_collision and damage_applier
+#[allow(clippy::type_complexity)]
+pub(crate) fn calculate_damage(
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut ev_damage: EventReader<DamageEvent>,
+    mut ev_calculated: EventWriter<CalculatedDamageEvent>,
+    player: Query<(&Damage, &PlayerStats), With<PlayerMarker>>,
+    player_sources: Query<
+        (),
+        Or<(
+            With<PlayerProjectileMarker>,
+            With<AoEDamageMarker>,
+            With<PlayerSpellMarker>,
+        )>,
+    >,
+    resistances: Query<&Resistances>,
+) {
+    for ev in ev_damage.read() {
+        let mut damage = ev.damage as f32;
+        let mut crit = false;
+        if ev.on_hit && player_sources.contains(ev.source) {
+            if let Ok((flat, stats)) = player.get_single() {
+                damage = (damage + flat.0 as f32) * stats.get(Stat::Might);
+                let roll = rng.next_u32() as f32 / u32::MAX as f32;
+                if roll < stats.get(Stat::CritChance) {
+                    crit = true;
+                    damage *= stats.get(Stat::CritDamage);
+                }
+            }
+        }
+        if let Ok(resistances) = resistances.get(ev.target) {
+            damage *= resistances.multiplier(ev.damage_type);
+        }
+        // a hit that isn't fully resisted always deals at least 1 damage
+        let damage = if damage > 0.0 {
+            damage.round().max(1.0) as usize
+        } else {
+            0
+        };
+        ev_calculated.send(CalculatedDamageEvent {
+            damage,
+            target: ev.target,
+            source: ev.source,
+            on_hit: ev.on_hit,
+            crit,
+            damage_type: ev.damage_type,
+        });
+    }
+}
@@ -0,0 +1,140 @@
+use crate::move_player;
+use ar_core::{
+    AppState, BattleSet, CurrentStamina, DashUsage, Dashing, Layer, LifeTime, PlayerDash,
+    PlayerLastDirection, PlayerMarker, PlayerStats, StatusEffects,
+};
+use avian2d::{prelude::*, schedule::PhysicsSchedule, schedule::PhysicsStepSet};
+use bevy::prelude::*;
+
+/// The interval between the afterimages left behind by the dash
+const TRAIL_INTERVAL: f32 = 0.03;
+/// How long an afterimage stays on screen
+const TRAIL_LIFETIME: f32 = 0.2;
+
+pub struct DashPlugin;
+
+impl Plugin for DashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PhysicsSchedule,
+            (
+                start_dash.before(move_player),
+                apply_dash.after(move_player),
+            )
+                .before(PhysicsStepSet::First)
+                .run_if(in_state(AppState::InBattle)),
+        )
+        .add_systems(FixedUpdate, tick_dash_cooldown.in_set(BattleSet));
+    }
+}
+
+/// Starts a dash in the last direction the player faced,
+/// if the cooldown is over and the player has enough stamina
+#[allow(clippy::type_complexity)]
+fn start_dash(
+    mut commands: Commands,
+    mut ev_dash: EventReader<DashUsage>,
+    mut dash: ResMut<PlayerDash>,
+    player_last_direction: Res<PlayerLastDirection>,
+    mut player: Query<
+        (
+            Entity,
+            &mut CurrentStamina,
+            &mut CollisionLayers,
+            &StatusEffects,
+        ),
+        (With<PlayerMarker>, Without<Dashing>),
+    >,
+) {
+    if ev_dash.is_empty() {
+        return;
+    }
+    ev_dash.clear();
+    let Ok((player_id, mut stamina, mut layers, effects)) = player.get_single_mut() else {
+        return;
+    };
+    if !dash.timer.finished() || stamina.0 < dash.stamina_cost || effects.is_stunned() {
+        return;
+    }
+    stamina.0 -= dash.stamina_cost;
+    dash.timer.reset();
+    dash.dashing = true;
+
+    let previous_layers = *layers;
+    if dash.pass_through {
+        layers.filters.remove(Layer::Monster);
+    }
+    let mut direction = player_last_direction.direction.normalize_or_zero();
+    if direction == Vec2::ZERO {
+        direction = Vec2::Y;
+    }
+    commands.entity(player_id).insert(Dashing {
+        direction,
+        timer: Timer::from_seconds(dash.duration, TimerMode::Once),
+        trail: Timer::from_seconds(TRAIL_INTERVAL, TimerMode::Repeating),
+        layers: previous_layers,
+    });
+}
+
+/// Moves the player at the speed needed to cover the dash distance in its duration,
+/// leaving a trail of afterimages behind
+#[allow(clippy::type_complexity)]
+fn apply_dash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut dash: ResMut<PlayerDash>,
+    mut player: Query<
+        (
+            Entity,
+            &mut Dashing,
+            &mut LinearVelocity,
+            &mut CollisionLayers,
+            &Sprite,
+            &Transform,
+        ),
+        With<PlayerMarker>,
+    >,
+) {
+    let Ok((player_id, mut dashing, mut linear_vel, mut layers, sprite, transform)) =
+        player.get_single_mut()
+    else {
+        return;
+    };
+    linear_vel.0 = dashing.direction * dash.distance / dash.duration;
+
+    if dashing.trail.tick(time.delta()).just_finished() {
+        commands
+            .spawn(Sprite {
+                color: Color::srgba(1.0, 1.0, 1.0, 0.4),
+                ..sprite.clone()
+            })
+            .insert(Transform {
+                translation: transform.translation.with_z(transform.translation.z - 1.0),
+                ..*transform
+            })
+            .insert(LifeTime {
+                timer: Timer::from_seconds(TRAIL_LIFETIME, TimerMode::Once),
+            });
+    }
+
+    if dashing.timer.tick(time.delta()).finished() {
+        *layers = dashing.layers;
+        // ends the burst instead of letting the player slide
+        linear_vel.0 *= 0.25;
+        dash.dashing = false;
+        commands.entity(player_id).remove::<Dashing>();
+    }
+}
+
+/// Ticks the cooldown of the dash, scaled by the player's cooldown stat
+fn tick_dash_cooldown(
+    time: Res<Time>,
+    mut dash: ResMut<PlayerDash>,
+    player: Query<&PlayerStats, With<PlayerMarker>>,
+) {
+    if dash.dashing {
+        return;
+    }
+    let cooldown = player.get_single().map_or(1.0, |stats| stats.cooldown());
+    dash.timer.tick(time.delta().div_f32(cooldown));
+}
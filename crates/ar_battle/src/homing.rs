@@ -0,0 +1,41 @@
+use crate::bounce::nearest_unhit_direction;
+use ar_core::{BattleSet, CollidedHash, Homing, MonsterMarker, PlayerProjectileMarker};
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+pub struct HomingPlugin;
+
+impl Plugin for HomingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, steer_homing_projectiles.in_set(BattleSet));
+    }
+}
+
+/// Turns the velocity of the homing projectiles towards the nearest monster
+/// they haven't hit yet, limited by their turn rate, the speed is kept
+#[allow(clippy::type_complexity)]
+fn steer_homing_projectiles(
+    time: Res<Time>,
+    mut projectiles: Query<
+        (&Homing, &CollidedHash, &Transform, &mut LinearVelocity),
+        With<PlayerProjectileMarker>,
+    >,
+    monsters: Query<(Entity, &Transform), With<MonsterMarker>>,
+) {
+    for (homing, collided, transform, mut velocity) in projectiles.iter_mut() {
+        if velocity.0 == Vec2::ZERO {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let monsters = monsters
+            .iter()
+            .map(|(entity, t)| (entity, t.translation.truncate()));
+        let Some(direction) = nearest_unhit_direction(position, collided, monsters, homing.radius)
+        else {
+            continue;
+        };
+        let max_turn = homing.turn_rate * time.delta_secs();
+        let angle = velocity.0.angle_to(direction).clamp(-max_turn, max_turn);
+        velocity.0 = Vec2::from_angle(angle).rotate(velocity.0);
+    }
+}
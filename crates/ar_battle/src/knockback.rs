@@ -0,0 +1,71 @@
+use crate::damage_applier;
+use ar_core::{AppState, BattleSet, KnockbackResistance, Staggered};
+use avian2d::{prelude::*, schedule::PhysicsSchedule, schedule::PhysicsStepSet};
+use bevy::prelude::*;
+
+/// How long the AI stops steering the target after a knockback
+const STAGGER_DURATION: f32 = 0.25;
+
+pub struct KnockbackPlugin;
+
+impl Plugin for KnockbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KnockbackEvent>()
+            .add_systems(
+                PhysicsSchedule,
+                apply_knockback
+                    .after(damage_applier)
+                    .before(PhysicsStepSet::First)
+                    .run_if(in_state(AppState::InBattle)),
+            )
+            .add_systems(FixedUpdate, tick_stagger.in_set(BattleSet));
+    }
+}
+
+/// An event to push the target away from a position
+#[derive(Event)]
+pub struct KnockbackEvent {
+    pub target: Entity,
+    pub origin: Vec2,
+    pub force: f32,
+}
+
+/// Applies an impulse to the target, away from the origin of the hit,
+/// the resulting velocity is scaled by the target's mass and resistance
+fn apply_knockback(
+    mut commands: Commands,
+    mut ev_knockback: EventReader<KnockbackEvent>,
+    mut targets: Query<(
+        &GlobalTransform,
+        &mut ExternalImpulse,
+        Option<&KnockbackResistance>,
+    )>,
+) {
+    for ev in ev_knockback.read() {
+        let Ok((transform, mut impulse, resistance)) = targets.get_mut(ev.target) else {
+            continue;
+        };
+        let resistance = resistance.map_or(0.0, |r| r.0.clamp(0.0, 1.0));
+        if resistance >= 1.0 {
+            continue;
+        }
+        let direction = (transform.translation().truncate() - ev.origin).normalize_or_zero();
+        impulse.apply_impulse(direction * ev.force * (1.0 - resistance));
+        // the target may be despawned by a killing blow in this same frame
+        commands.entity(ev.target).try_insert(Staggered {
+            timer: Timer::from_seconds(STAGGER_DURATION, TimerMode::Once),
+        });
+    }
+}
+
+fn tick_stagger(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Staggered)>,
+) {
+    for (entity, mut staggered) in query.iter_mut() {
+        if staggered.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Staggered>();
+        }
+    }
+}
@@ -1,12 +1,42 @@
+pub mod aura;
+pub mod beam;
+pub mod bounce;
+pub mod buffs;
+pub mod chain;
+pub mod damage;
+pub mod dash;
+pub mod homing;
+pub mod knockback;
+pub mod orbit;
+pub mod stats;
+pub mod status;
+pub mod trap;
+pub mod triggers;
+
+use crate::aura::AuraPlugin;
+use crate::beam::BeamPlugin;
+use crate::bounce::{ricochet_direction, BouncePlugin};
+use crate::buffs::BuffsPlugin;
+use crate::chain::ChainPlugin;
+use crate::damage::{calculate_damage, CalculatedDamageEvent};
+use crate::dash::DashPlugin;
+use crate::homing::HomingPlugin;
+use crate::knockback::{KnockbackEvent, KnockbackPlugin};
+use crate::orbit::OrbitPlugin;
+use crate::stats::PlayerStatsPlugin;
+use crate::status::StatusEffectsPlugin;
+use crate::trap::TrapPlugin;
+use crate::triggers::TriggersPlugin;
 use ar_core::{
-    AppState, BattleSet, BoostUsage, CollidedHash, CurrentStamina, Damage, DashUsage, DeathEvent,
-    DisplayDamageEvent, DropItemEvent, DropsChance, Health, Layer, LifeTime, LootTables,
-    MagnetMarker, MaxStamina, MonsterMarker, MonsterProjectileMarker, Penetration, PickupEvent,
-    PlayerDirection, PlayerInvulnerableFrames, PlayerLastDirection, PlayerMarker,
-    PlayerMinusHpEvent, PlayerProjectileMarker, ProjectilePattern, StaminaRegen,
-    SpellAOEType,
+    ActiveBuffs, AoEAnchor, AppState, ApplyStatusEffectEvent, Armor, BattleSet, BoostUsage, Bounce,
+    CollidedHash, CurrentStamina, Damage, DamageType, DamageZone, Dashing, DeathEvent,
+    DisplayDamageEvent, DropItemEvent, DropsChance, Health, Knockback, Layer, LifeTime, LootTables,
+    MagnetMarker, MaxStamina, MonsterMarker, MonsterProjectileMarker, OnHitEffect, Penetration,
+    PickupEvent, PlayerDirection, PlayerInvulnerableFrames, PlayerLastDirection, PlayerMarker,
+    PlayerMinusHpEvent, PlayerProjectileMarker, PlayerStats, ProjectilePattern, Ricochet, Shield,
+    SpellAOEType, StaminaRegen, Stat, StatusEffects,
 };
-use ar_spells::generator::{AOESpells, OwnedAOESpells, OwnedProjectileSpells, ProjectileSpells};
+use ar_spells::generator::{AOESpells, OwnedAOESpells, OwnedProjectileSpells};
 use avian2d::{prelude::*, schedule::PhysicsSchedule, schedule::PhysicsStepSet};
 use bevy::prelude::*;
 use bevy::utils::{HashMap, HashSet};
@@ -33,11 +63,38 @@ pub struct SpellsSheetSmall {
     pub sprite: HashMap<AssetFileStem, Handle<Image>>,
 }
 
+/// The sprite of a spell stretched to the size,
+/// or a plain rectangle of the fallback color if the spell's image doesn't exist
+pub(crate) fn spell_sprite(image: Option<&Handle<Image>>, size: Vec2, fallback: Color) -> Sprite {
+    match image {
+        Some(image) => Sprite {
+            image: image.clone(),
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        None => Sprite::from_color(fallback, size),
+    }
+}
+
 impl Plugin for BattlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerDamageEvent>()
+        app.add_plugins(BuffsPlugin)
+            .add_plugins(StatusEffectsPlugin)
+            .add_plugins(KnockbackPlugin)
+            .add_plugins(PlayerStatsPlugin)
+            .add_plugins(DashPlugin)
+            .add_plugins(BouncePlugin)
+            .add_plugins(HomingPlugin)
+            .add_plugins(ChainPlugin)
+            .add_plugins(BeamPlugin)
+            .add_plugins(OrbitPlugin)
+            .add_plugins(TrapPlugin)
+            .add_plugins(AuraPlugin)
+            .add_plugins(TriggersPlugin)
+            .add_event::<PlayerDamageEvent>()
             .add_event::<PlayerMinusHpEvent>()
             .add_event::<DamageEvent>()
+            .add_event::<CalculatedDamageEvent>()
             .add_event::<DisplayDamageEvent>()
             .add_event::<DeathEvent>()
             .add_event::<DropItemEvent>()
@@ -49,6 +106,8 @@ impl Plugin for BattlePlugin {
                 (
                     move_player,
                     handle_collision,
+                    player_contact_damage,
+                    calculate_damage,
                     player_damaged_handler,
                     damage_applier,
                     death_applier,
@@ -64,6 +123,7 @@ impl Plugin for BattlePlugin {
                     queue_spawn_player_projectiles.in_set(BattleSet),
                     spawn_player_projectiles.in_set(BattleSet),
                     regenerate_stamina.in_set(BattleSet),
+                    regenerate_shield.in_set(BattleSet),
                     handle_magnet_collision.in_set(BattleSet),
                     queue_spawn_player_aoe.in_set(BattleSet),
                 )
@@ -72,32 +132,53 @@ impl Plugin for BattlePlugin {
             .add_systems(Update, create_aoe_spell.in_set(BattleSet))
             .add_systems(
                 PhysicsSchedule,
-                (
-                    handle_aoe_collisions,
-                    aoe_damage_handler,
-                )
-                .chain()
-                .after(PhysicsStepSet::Last)
-                .run_if(in_state(AppState::InBattle))
+                aoe_damage_handler
+                    .after(PhysicsStepSet::Last)
+                    .run_if(in_state(AppState::InBattle)),
             );
     }
 }
 
-/// Changes the player's LinearVelocity based on input.
+/// The stamina drained each second while boosting
+const BOOST_STAMINA_COST: f32 = 2.0;
+/// The speed added to the player while boosting
+const BOOST_SPEED: f32 = 50.0;
+
+/// Changes the player's LinearVelocity based on input,
+/// the input is ignored while the player is dashing
+#[allow(clippy::type_complexity)]
 fn move_player(
-    mut q: Query<&mut LinearVelocity, With<PlayerMarker>>,
+    time: Res<Time>,
+    mut q: Query<
+        (
+            &mut LinearVelocity,
+            &mut CurrentStamina,
+            &PlayerStats,
+            &StatusEffects,
+            Has<Dashing>,
+        ),
+        With<PlayerMarker>,
+    >,
     mut ev_direction: EventReader<PlayerDirection>,
     mut ev_boost: EventReader<BoostUsage>,
-    mut ev_dash: EventReader<DashUsage>,
     mut player_last_direction: ResMut<PlayerLastDirection>,
 ) {
-    let mut linear_vel = q.single_mut();
+    let (mut linear_vel, mut stamina, stats, effects, dashing) = q.single_mut();
+    if dashing {
+        ev_direction.clear();
+        ev_boost.clear();
+        return;
+    }
     // decelerates the player
     linear_vel.x *= 0.90;
     linear_vel.y *= 0.90;
+    if effects.is_stunned() {
+        ev_direction.clear();
+        ev_boost.clear();
+        return;
+    }
     let direction: Vec2;
     let mut boost: f32 = 0.0;
-    let mut dash: f32 = 0.0;
     if ev_direction.is_empty() {
         return;
     } else {
@@ -107,15 +188,16 @@ fn move_player(
         player_last_direction.direction = direction;
     }
     if !ev_boost.is_empty() {
-        boost = 5.;
-    }
-    if !ev_dash.is_empty() {
-        dash = 5.;
+        let cost = BOOST_STAMINA_COST * time.delta_secs();
+        if stamina.0 >= cost {
+            stamina.0 -= cost;
+            boost = BOOST_SPEED;
+        }
     }
     ev_boost.clear();
-    ev_dash.clear();
-    linear_vel.x += direction.x * 10.0 * (2.5 + boost + dash);
-    linear_vel.y += direction.y * 10.0 * (2.5 + boost + dash);
+    let speed = (stats.get(Stat::MoveSpeed) + boost) * effects.speed_multiplier();
+    linear_vel.x += direction.x * speed;
+    linear_vel.y += direction.y * speed;
 }
 
 #[derive(Event)]
@@ -123,6 +205,10 @@ pub struct DamageEvent {
     pub damage: usize,
     pub target: Entity,
     pub source: Entity,
+    // Whether the damage comes from a hit, applying the on-hit effects of the source,
+    // damage over time is not a hit
+    pub on_hit: bool,
+    pub damage_type: DamageType,
 }
 
 #[derive(Event)]
@@ -144,46 +230,25 @@ fn handle_magnet_collision(
     }
 }
 
-/// Handles the possible collisions accordingly to Layers' rules,
-/// Player only gets damaged by the largest damage source possible
-// TODO! Instead of doing this, add CollidingEntities for each source of continuous
-// damage/check
-#[allow(clippy::too_many_arguments)]
+/// Handles the collisions of the player's projectiles accordingly to Layers' rules,
+/// the damage to the player is handled by player_contact_damage
 fn handle_collision(
     mut ev_collision_reader: EventReader<CollisionStarted>,
     mut ev_damage: EventWriter<DamageEvent>,
-    mut ev_player_damage: EventWriter<PlayerDamageEvent>,
     damage: Query<&Damage>,
+    damage_type: Query<&DamageType>,
     monster_query: Query<Entity, With<MonsterMarker>>,
     monster_projectile_query: Query<Entity, With<MonsterProjectileMarker>>,
     mut player_projectile_query: Query<(Entity, &mut CollidedHash), With<PlayerProjectileMarker>>,
-    player_query: Query<Entity, With<PlayerMarker>>,
 ) {
     if ev_collision_reader.is_empty() {
         return;
     }
-    // The largest damage given to the player this frame
-    let mut player_damage = 0;
-    let mut source: Entity = Entity::from_raw(0);
     for CollisionStarted(entity1, entity2) in ev_collision_reader.read() {
         let entity1 = *entity1;
         let entity2 = *entity2;
 
-        if player_query.contains(entity1) {
-            if let Ok(damage) = damage.get(entity2) {
-                if player_damage < damage.0 {
-                    player_damage = damage.0;
-                    source = entity2;
-                }
-            }
-        } else if player_query.contains(entity2) {
-            if let Ok(damage) = damage.get(entity1) {
-                if player_damage < damage.0 {
-                    player_damage = damage.0;
-                    source = entity1;
-                }
-            }
-        } else if monster_query.contains(entity1) {
+        if monster_query.contains(entity1) {
             if player_projectile_query.contains(entity2) {
                 // Unwrap safety: It is guaranteed to have the entity as we just checked in the 'if'
                 let (_, mut collided) = player_projectile_query.get_mut(entity2).unwrap();
@@ -196,6 +261,8 @@ fn handle_collision(
                     damage: damage.get(entity2).unwrap().0,
                     target: entity1,
                     source: entity2,
+                    on_hit: true,
+                    damage_type: damage_type.get(entity2).copied().unwrap_or_default(),
                 });
             }
         } else if monster_query.contains(entity2) {
@@ -211,6 +278,8 @@ fn handle_collision(
                     damage: damage.get(entity1).unwrap().0,
                     target: entity2,
                     source: entity1,
+                    on_hit: true,
+                    damage_type: damage_type.get(entity1).copied().unwrap_or_default(),
                 });
             }
         } else if (monster_projectile_query.contains(entity1)
@@ -222,46 +291,93 @@ fn handle_collision(
                 damage: damage.get(entity1).unwrap().0,
                 target: entity1,
                 source: entity2,
+                on_hit: true,
+                damage_type: damage_type.get(entity2).copied().unwrap_or_default(),
             });
             ev_damage.send(DamageEvent {
                 damage: damage.get(entity2).unwrap().0,
                 target: entity2,
                 source: entity1,
+                on_hit: true,
+                damage_type: damage_type.get(entity1).copied().unwrap_or_default(),
             });
         }
     }
-    if player_damage > 0 {
-        ev_player_damage.send(PlayerDamageEvent {
-            damage: player_damage,
-            target: player_query.single(),
-            source,
-        });
+}
+
+/// Damages the player with the strongest source overlapping it, i.e., monsters,
+/// their projectiles and damage zones, every tick the player stays in contact,
+/// so the damage is applied again every time the invulnerability frames end
+#[allow(clippy::type_complexity)]
+fn player_contact_damage(
+    player: Query<(Entity, &CollidingEntities), With<PlayerMarker>>,
+    damage: Query<
+        &Damage,
+        Or<(
+            With<MonsterMarker>,
+            With<MonsterProjectileMarker>,
+            With<DamageZone>,
+        )>,
+    >,
+    mut ev_player_damage: EventWriter<PlayerDamageEvent>,
+) {
+    let Ok((player_id, colliding)) = player.get_single() else {
+        return;
+    };
+    let strongest = colliding
+        .iter()
+        .filter_map(|entity| damage.get(*entity).ok().map(|damage| (*entity, damage.0)))
+        .max_by_key(|(_, damage)| *damage);
+    if let Some((source, damage)) = strongest {
+        if damage > 0 {
+            ev_player_damage.send(PlayerDamageEvent {
+                damage,
+                target: player_id,
+                source,
+            });
+        }
     }
 }
 
 fn player_damaged_handler(
     time: Res<Time>,
     mut ev_damage: EventReader<PlayerDamageEvent>,
-    mut player_inv: Query<&mut PlayerInvulnerableFrames, With<PlayerMarker>>,
-    mut player_health: Query<&mut Health, With<PlayerMarker>>,
+    mut player_inv: Query<
+        (&mut PlayerInvulnerableFrames, &ActiveBuffs, Has<Dashing>),
+        With<PlayerMarker>,
+    >,
+    mut player_health: Query<(&mut Health, &Armor, &mut Shield), With<PlayerMarker>>,
     mut ev_player_damaged: EventWriter<PlayerMinusHpEvent>,
+    on_hit_effect: Query<&OnHitEffect>,
+    mut ev_status: EventWriter<ApplyStatusEffectEvent>,
 ) {
-    let mut inv = player_inv.single_mut();
+    let (mut inv, buffs, dashing) = player_inv.single_mut();
     inv.timer.tick(time.delta());
-    if inv.timer.just_finished() || !inv.timer.finished() {
+    // the dash only grants invulnerability for its own duration
+    if inv.timer.just_finished() || !inv.timer.finished() || buffs.invulnerable() || dashing {
         ev_damage.clear();
         return;
     }
-    if ev_damage.is_empty() {
+    // only the strongest hit of the frame lands, once per invulnerability window
+    let Some(ev) = ev_damage.read().max_by_key(|ev| ev.damage) else {
         return;
-    }
-    let mut player_health = player_health.single_mut();
-    for ev in ev_damage.read() {
-        if player_health.0 <= ev.damage {
+    };
+    let (mut player_health, armor, mut shield) = player_health.single_mut();
+    let damage = shield.absorb(armor.mitigate(ev.damage));
+    // when fully absorbed by the shield, the on-hit effects are absorbed too
+    if damage > 0 {
+        if player_health.0 <= damage {
             player_health.0 = 0;
         } else {
-            player_health.0 -= ev.damage;
-            ev_player_damaged.send(PlayerMinusHpEvent { damage: ev.damage });
+            player_health.0 -= damage;
+            ev_player_damaged.send(PlayerMinusHpEvent { damage });
+        }
+        if let Ok(effect) = on_hit_effect.get(ev.source) {
+            ev_status.send(ApplyStatusEffectEvent {
+                target: ev.target,
+                source: ev.source,
+                effect: effect.0.clone(),
+            });
         }
     }
     inv.timer.reset();
@@ -272,20 +388,23 @@ fn player_damaged_handler(
 fn queue_spawn_player_projectiles(
     mut commands: Commands,
     time: Res<Time>,
-    mut projectiles: Query<&mut OwnedProjectileSpells, With<PlayerMarker>>,
+    mut projectiles: Query<(&mut OwnedProjectileSpells, &PlayerStats), With<PlayerMarker>>,
 ) {
     if projectiles.is_empty() {
         return;
     }
 
-    let mut projectiles = projectiles.single_mut();
+    let (mut projectiles, stats) = projectiles.single_mut();
+    let extra_projectiles = stats.get(Stat::Amount) as u8;
+    let delta = time.delta().div_f32(stats.cooldown());
     for proj in projectiles.spells.iter_mut() {
-        if !proj.cooldown.tick(time.delta()).finished() {
+        if !proj.cooldown.tick(delta).finished() {
             continue;
         }
-        for i in 0..proj.count {
+        let count = proj.count.saturating_add(extra_projectiles);
+        for i in 0..count {
             let time_to_spawn: f32 = (i as f32 + 1.0) * 2.0 / 3.0
-                * (proj.cooldown.duration().as_secs_f32() / proj.count as f32);
+                * (proj.cooldown.duration().as_secs_f32() / count as f32);
             let angle = match proj.pattern {
                 ProjectilePattern::Circle => Vec2::ZERO,
                 ProjectilePattern::Line => Vec2::ZERO,
@@ -299,87 +418,158 @@ fn queue_spawn_player_projectiles(
     }
 }
 
-/// Creates a new aoe entity that will collide with monsters
+/// Creates a new aoe entity that will collide with monsters,
+/// at the position defined by the spell's anchor
+#[allow(clippy::too_many_arguments)]
 fn queue_spawn_player_aoe(
     mut commands: Commands,
     time: Res<Time>,
-    mut aoe: Query<&mut OwnedAOESpells, With<PlayerMarker>>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut aoe: Query<(&mut OwnedAOESpells, &Transform, &PlayerStats), With<PlayerMarker>>,
+    monsters: Query<&Transform, With<MonsterMarker>>,
+    player_last_direction: Res<PlayerLastDirection>,
+    sprite_sheet: Res<SpellsSheetSmall>,
 ) {
     if aoe.is_empty() {
         return;
     }
 
-    let mut aoe = aoe.single_mut();
+    let (mut aoe, player_transform, stats) = aoe.single_mut();
+    let player_position = player_transform.translation.truncate();
+    let delta = time.delta().div_f32(stats.cooldown());
+    let area = stats.get(Stat::Area);
+    let duration_multiplier = stats.get(Stat::Duration);
     for aoe in aoe.spells.iter_mut() {
-        if !aoe.cooldown.tick(time.delta()).finished() {
+        if !aoe.cooldown.tick(delta).finished() {
             continue;
         }
-        let aoe_collider = match aoe.pattern {
-            SpellAOEType::Circle => {
-                Collider::circle(aoe.radius)
-            }
-            SpellAOEType::Rectangle => {
-                Collider::rectangle(aoe.radius, aoe.radius)
+        let radius = aoe.radius * area;
+        let (aoe_collider, size) = match aoe.pattern {
+            SpellAOEType::Circle => (Collider::circle(radius), Vec2::splat(radius * 2.0)),
+            SpellAOEType::Rectangle => (Collider::rectangle(radius, radius), Vec2::splat(radius)),
+            SpellAOEType::Arc(x) => (
+                Collider::ellipse(radius * x, radius * (1.0 / x)),
+                Vec2::new(radius * x * 2.0, radius * (1.0 / x) * 2.0),
+            ),
+        };
+        let position = match aoe.anchor {
+            AoEAnchor::Player => player_position,
+            AoEAnchor::AimPoint(distance) => {
+                player_position + player_last_direction.direction.normalize_or_zero() * distance
             }
-            SpellAOEType::Arc(x) => {
-                Collider::ellipse(aoe.radius * x , aoe.radius * (1.0 / x ) )
+            AoEAnchor::RandomMonster(range) => {
+                let in_range: Vec<Vec2> = monsters
+                    .iter()
+                    .map(|t| t.translation.truncate())
+                    .filter(|p| p.distance_squared(player_position) <= range * range)
+                    .collect();
+                if in_range.is_empty() {
+                    player_position
+                } else {
+                    in_range[(rng.next_u32() as usize) % in_range.len()]
+                }
             }
         };
-        let damage = aoe.damage;
-        let distributed = aoe.distributed;
-        //let knockback = aoe.knockback;
-        let physical_layer = CollisionLayers::new(
-            [Layer::PlayerAOE],
-            [Layer::Monster],
+        let sprite = spell_sprite(
+            sprite_sheet.sprite.get(aoe.sprite.as_str()),
+            size,
+            Color::srgba_u8(155, 188, 15, 96),
         );
+        let physical_layer = CollisionLayers::new([Layer::PlayerAOE], [Layer::Monster]);
 
-        commands
+        let aoe_id = commands
             .spawn_empty()
             .insert(aoe_collider)
+            .insert(sprite)
+            .insert(Transform::from_translation(position.extend(4.0)))
             .insert(AoEDamageMarker)
             .insert(AoEDamage {
-                damage,
-                distributed,
+                damage: aoe.damage,
+                distributed: aoe.distributed,
             })
+            .insert(aoe.damage_type)
             .insert(physical_layer)
-            .insert(CollidedHash(HashSet::with_capacity(16)))
-            .insert(RigidBody::Static);
-    }
-}
-
-/// Must be run after the physics pipeline and before aoe_damage_handler system
-fn handle_aoe_collisions(
-    mut aoe_query: Query<(&CollidingEntities, &mut CollidedHash), With<AoEDamageMarker>>,
-) {
-    for (colliding, mut collided) in aoe_query.iter_mut() {
-        for entity in colliding.iter() {
-                collided.0.insert(*entity);
+            .insert(CollidingEntities::default())
+            .insert(Sensor)
+            .insert(RigidBody::Static)
+            .id();
+        if let Some(knockback) = aoe.knockback {
+            commands.entity(aoe_id).insert(Knockback(knockback));
+        }
+        if let Some(effect) = &aoe.effect {
+            commands.entity(aoe_id).insert(OnHitEffect(effect.clone()));
+        }
+        if let Some(duration) = aoe.duration {
+            commands.entity(aoe_id).insert(AoEDuration {
+                lifetime: Timer::from_seconds(duration * duration_multiplier, TimerMode::Once),
+                tick: Timer::from_seconds(aoe.tick_rate, TimerMode::Repeating),
+            });
         }
     }
 }
 
-/// Sends events to damage the target of the player's AoE spells
-/// This system must be run after the Physics pipeline, and after the system that handles AoE CollidedHash components, as it assumes that all
-/// aoe entities had their collisions resolved in this frame to despawn them.
+/// Sends events to damage the target of the player's AoE spells,
+/// an aoe without a duration hits once and is despawned,
+/// otherwise it hits every tick until its duration ends
+/// This system must be run after the Physics pipeline, as it assumes that all
+/// aoe entities had their collisions resolved in this frame.
+#[allow(clippy::type_complexity)]
 fn aoe_damage_handler(
+    time: Res<Time>,
     mut despawn_event: EventWriter<DespawnEvent>,
     mut ev_damage: EventWriter<DamageEvent>,
-    query_aoe: Query<(Entity, &AoEDamage, &CollidedHash), With<AoEDamageMarker>>,
+    mut query_aoe: Query<
+        (
+            Entity,
+            &AoEDamage,
+            &CollidingEntities,
+            Option<&mut AoEDuration>,
+        ),
+        With<AoEDamageMarker>,
+    >,
+    monsters: Query<(), With<MonsterMarker>>,
+    damage_type: Query<&DamageType>,
 ) {
-    for (source, aoe_damage, collided) in query_aoe.iter() {
+    for (source, aoe_damage, colliding, duration) in query_aoe.iter_mut() {
+        let hits = match duration {
+            None => {
+                despawn_event.send(DespawnEvent { entity: source });
+                true
+            }
+            Some(mut duration) => {
+                let first_tick = duration.lifetime.elapsed().is_zero();
+                duration.tick.tick(time.delta());
+                if duration.lifetime.tick(time.delta()).finished() {
+                    despawn_event.send(DespawnEvent { entity: source });
+                }
+                first_tick || duration.tick.just_finished()
+            }
+        };
+        if !hits {
+            continue;
+        }
+        let targets: Vec<Entity> = colliding
+            .iter()
+            .copied()
+            .filter(|e| monsters.contains(*e))
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
         let damage = if aoe_damage.distributed {
-            aoe_damage.damage / collided.0.len()
+            aoe_damage.damage / targets.len()
         } else {
             aoe_damage.damage
         };
-        for target in collided.0.iter() {
+        for target in targets {
             ev_damage.send(DamageEvent {
                 damage,
-                target: *target,
+                target,
                 source,
+                on_hit: true,
+                damage_type: damage_type.get(source).copied().unwrap_or_default(),
             });
         }
-        despawn_event.send(DespawnEvent { entity: source });
     }
 }
 
@@ -396,37 +586,117 @@ struct AoEDamage {
     distributed: bool,
 }
 
-/// Applies damage to the target,
+/// The lifetime of an aoe that persists, and the interval between its hits
+#[derive(Component)]
+struct AoEDuration {
+    lifetime: Timer,
+    tick: Timer,
+}
+
+/// Applies the calculated damage to the target after its armor and shield,
 /// except for the player
-fn damage_applier(
+/// A projectile that hits ricochets towards another monster if it can,
+/// otherwise it consumes its penetration
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn damage_applier(
     mut commands: Commands,
-    mut ev_damage: EventReader<DamageEvent>,
-    mut health: Query<&mut Health, Without<PlayerMarker>>,
+    mut ev_damage: EventReader<CalculatedDamageEvent>,
+    mut health: Query<
+        (
+            &mut Health,
+            Option<&Armor>,
+            Option<&mut Shield>,
+            Option<&GlobalTransform>,
+        ),
+        Without<PlayerMarker>,
+    >,
     mut death_event: EventWriter<DeathEvent>,
     mut display_damage: EventWriter<DisplayDamageEvent>,
-    mut player_projectile: Query<(Entity, &mut Penetration), With<PlayerProjectileMarker>>,
+    mut player_projectile: Query<
+        (
+            Entity,
+            &mut Penetration,
+            Option<&mut Ricochet>,
+            &CollidedHash,
+            &mut LinearVelocity,
+            &GlobalTransform,
+        ),
+        With<PlayerProjectileMarker>,
+    >,
+    monsters: Query<(Entity, &GlobalTransform), With<MonsterMarker>>,
+    on_hit_effect: Query<&OnHitEffect>,
+    mut ev_status: EventWriter<ApplyStatusEffectEvent>,
+    knockback: Query<(&Knockback, &GlobalTransform)>,
+    mut ev_knockback: EventWriter<KnockbackEvent>,
 ) {
     if ev_damage.is_empty() {
         return;
     }
     let mut despawned_projectiles = HashSet::new();
     for ev in ev_damage.read() {
-        if despawned_projectiles.contains(&ev.source) {
+        // the damage over time of an effect applied by the projectile still lands
+        if ev.on_hit && despawned_projectiles.contains(&ev.source) {
             continue;
         }
-        if let Ok(mut health) = health.get_mut(ev.target) {
-            if health.0 <= ev.damage {
-                death_event.send(DeathEvent { target: ev.target });
-            } else {
-                health.0 -= ev.damage;
+        let mut damage = ev.damage;
+        if let Ok((mut health, armor, shield, transform)) = health.get_mut(ev.target) {
+            if let Some(armor) = armor {
+                damage = armor.mitigate(damage);
+            }
+            if let Some(mut shield) = shield {
+                damage = shield.absorb(damage);
+            }
+            if damage > 0 {
+                if health.0 <= damage {
+                    death_event.send(DeathEvent {
+                        target: ev.target,
+                        position: transform.map_or(Vec3::ZERO, GlobalTransform::translation),
+                    });
+                } else {
+                    health.0 -= damage;
+                }
             }
         }
-        display_damage.send(DisplayDamageEvent {
-            damage: ev.damage,
-            target: ev.target,
-        });
-        if let Ok((projectile_id, mut pen)) = player_projectile.get_mut(ev.source) {
-            if pen.0 == 0 {
+        if damage > 0 {
+            display_damage.send(DisplayDamageEvent {
+                damage,
+                target: ev.target,
+                crit: ev.crit,
+                damage_type: ev.damage_type,
+            });
+        }
+        if !ev.on_hit {
+            continue;
+        }
+        if let Ok(effect) = on_hit_effect.get(ev.source) {
+            ev_status.send(ApplyStatusEffectEvent {
+                target: ev.target,
+                source: ev.source,
+                effect: effect.0.clone(),
+            });
+        }
+        if let Ok((knockback, transform)) = knockback.get(ev.source) {
+            ev_knockback.send(KnockbackEvent {
+                target: ev.target,
+                origin: transform.translation().truncate(),
+                force: knockback.0,
+            });
+        }
+        if let Ok((projectile_id, mut pen, ricochet, collided, mut velocity, transform)) =
+            player_projectile.get_mut(ev.source)
+        {
+            let position = transform.translation().truncate();
+            let retarget = ricochet.filter(|r| r.0 > 0).and_then(|ricochet| {
+                let monsters = monsters
+                    .iter()
+                    .map(|(entity, t)| (entity, t.translation().truncate()));
+                ricochet_direction(position, collided, monsters)
+                    .map(|direction| (ricochet, direction))
+            });
+            if let Some((mut ricochet, direction)) = retarget {
+                ricochet.0 -= 1;
+                velocity.0 = direction * velocity.0.length();
+            } else if pen.0 == 0 {
                 // as the command isn't applied until at least after the end of the function,
                 // it is safe to do so
                 commands.entity(ev.source).despawn_recursive();
@@ -452,17 +722,21 @@ fn death_applier(
     mut commands: Commands,
     mut rng: ResMut<GlobalEntropy<WyRand>>,
     item_query: Query<(&GlobalTransform, &LootTables, &DropsChance), With<MonsterMarker>>,
+    player: Query<&PlayerStats, With<PlayerMarker>>,
     mut ev_itemdrop: EventWriter<DropItemEvent>,
     mut ev_death: EventReader<DeathEvent>,
 ) {
     if ev_death.is_empty() {
         return;
     }
+    let luck = player
+        .get_single()
+        .map_or(1.0, |stats| stats.get(Stat::Luck));
     for ev in ev_death.read() {
         if let Ok((transform, table, chance)) = item_query.get(ev.target) {
             for i in 0..table.0.len() {
                 let random_number = rng.next_u32() % 100;
-                let chance = (chance.0 * 25.0).round() as u32;
+                let chance = (chance.0 * luck * 25.0).round() as u32;
                 if random_number <= chance {
                     ev_itemdrop.send(DropItemEvent {
                         position: transform.translation(),
@@ -486,17 +760,17 @@ struct PlayerProjectileSpawner {
 fn spawn_player_projectiles(
     mut commands: Commands,
     time: Res<Time>,
-    player_position: Query<&Transform, With<PlayerMarker>>,
+    player_position: Query<(&Transform, &PlayerStats, &OwnedProjectileSpells), With<PlayerMarker>>,
     mut spawner: Query<(Entity, &mut PlayerProjectileSpawner)>,
     sprite_sheet: Res<SpellsSheetSmall>,
-    spell: Res<ProjectileSpells>,
     player_last_direction: Res<PlayerLastDirection>,
 ) {
     if spawner.is_empty() {
         return;
     }
     let player_last_direction = player_last_direction.direction;
-    let player_transform = player_position.single();
+    let (player_transform, stats, owned_spells) = player_position.single();
+    let duration = stats.get(Stat::Duration);
     for (entity, mut spa) in spawner.iter_mut() {
         if !spa.timer.tick(time.delta()).just_finished() {
             continue;
@@ -507,10 +781,16 @@ fn spawn_player_projectiles(
         if dir == Vec2::ZERO {
             dir = Vec2::X;
         }
-        let proj = spell
-            .projectile_spells
-            .get(&spa.spell_name)
-            .unwrap_or_else(|| panic!("{} not found", spa.spell_name));
+        // the owned spell, as it may have been leveled up,
+        // or not owned anymore if it evolved before the projectile was shot
+        let Some(proj) = owned_spells
+            .spells
+            .iter()
+            .find(|s| s.name == spa.spell_name)
+        else {
+            commands.entity(entity).despawn();
+            continue;
+        };
         let linear_vel = dir * proj.projectile_movespeed;
         let sprite = proj.sprite.clone();
         commands
@@ -533,17 +813,43 @@ fn spawn_player_projectiles(
             .insert(Collider::circle(proj.radius))
             .insert(CollisionLayers::new(
                 [Layer::PlayerProjectile],
-                [Layer::Monster, Layer::MonsterProjectile],
+                [Layer::Monster, Layer::MonsterProjectile, Layer::Wall],
             ))
             .insert(Damage(proj.damage))
+            .insert(proj.damage_type)
             .insert(Penetration(proj.penetration))
+            .insert(Bounce(proj.bounce))
+            .insert(Ricochet(proj.ricochet))
             .insert(LifeTime {
-                timer: Timer::from_seconds(proj.lifetime, TimerMode::Once),
+                timer: Timer::from_seconds(proj.lifetime * duration, TimerMode::Once),
             })
             .insert(CollidedHash(HashSet::with_capacity(
                 proj.penetration.into(),
             )))
             .remove::<PlayerProjectileSpawner>();
+        if let Some(effect) = &proj.effect {
+            commands.entity(entity).insert(OnHitEffect(effect.clone()));
+        }
+        if let Some(knockback) = proj.knockback {
+            commands.entity(entity).insert(Knockback(knockback));
+        }
+        if let Some(homing) = proj.homing {
+            commands.entity(entity).insert(homing);
+        }
+    }
+}
+
+/// Regenerates the shield once its regeneration delay is over
+fn regenerate_shield(mut shield_query: Query<&mut Shield>, time: Res<Time>) {
+    for mut shield in shield_query.iter_mut() {
+        if shield.current >= shield.max {
+            continue;
+        }
+        if !shield.regen_delay.tick(time.delta()).finished() {
+            continue;
+        }
+        shield.current =
+            (shield.current + shield.regen * time.delta().as_secs_f32()).min(shield.max);
     }
 }
 
@@ -553,7 +859,7 @@ fn regenerate_stamina(
 ) {
     for (mut stamina, max_stamina, regen) in stamina_query.iter_mut() {
         if stamina.0 < max_stamina.0 {
-            stamina.0 = (stamina.0 + regen.0 * time.delta().as_secs_f32()).max(max_stamina.0);
+            stamina.0 = (stamina.0 + regen.0 * time.delta().as_secs_f32()).min(max_stamina.0);
         }
     }
 }
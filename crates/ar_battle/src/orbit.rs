@@ -0,0 +1,160 @@
+use crate::{spell_sprite, DamageEvent, SpellsSheetSmall};
+use ar_core::{
+    BattleSet, Damage, DamageType, Knockback, Layer, LifeTime, MonsterMarker, PlayerMarker,
+    PlayerSpellMarker, PlayerStats, Stat,
+};
+use ar_spells::generator::OwnedOrbitSpells;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::f32::consts::TAU;
+
+pub struct OrbitPlugin;
+
+impl Plugin for OrbitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (cast_player_orbits, move_orbits, orbit_hits)
+                .chain()
+                .in_set(BattleSet),
+        );
+    }
+}
+
+/// A projectile circling the player
+#[derive(Component)]
+struct Orbit {
+    angle: f32,
+    distance: f32,
+    speed: f32,
+}
+
+/// The monsters hit by an orbiting projectile, each one can't be hit again
+/// until its timer finishes, unlike the CollidedHash of the other projectiles
+#[derive(Component)]
+struct OrbitHits {
+    cooldown: f32,
+    hits: HashMap<Entity, Timer>,
+}
+
+/// Spawns the projectiles of the player's orbit spells when their cooldown finishes,
+/// evenly spaced around the player
+fn cast_player_orbits(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player: Query<(&mut OwnedOrbitSpells, &Transform, &PlayerStats), With<PlayerMarker>>,
+    sprite_sheet: Res<SpellsSheetSmall>,
+) {
+    let Ok((mut orbits, player_transform, stats)) = player.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta().div_f32(stats.cooldown());
+    let area = stats.get(Stat::Area);
+    let duration_multiplier = stats.get(Stat::Duration);
+    let extra_projectiles = stats.get(Stat::Amount) as u8;
+    for orbit in orbits.spells.iter_mut() {
+        if !orbit.cooldown.tick(delta).just_finished() {
+            continue;
+        }
+        let count = orbit.count.saturating_add(extra_projectiles).max(1);
+        let distance = orbit.distance * area;
+        for i in 0..count {
+            let angle = TAU * i as f32 / count as f32;
+            let position =
+                player_transform.translation.truncate() + Vec2::from_angle(angle) * distance;
+            let sprite = spell_sprite(
+                sprite_sheet.sprite.get(orbit.sprite.as_str()),
+                Vec2::splat(orbit.radius * 2.0),
+                Color::srgba_u8(200, 200, 255, 255),
+            );
+            let orbit_id = commands
+                .spawn(sprite)
+                .insert(Transform::from_translation(position.extend(4.0)))
+                .insert(Orbit {
+                    angle,
+                    distance,
+                    speed: orbit.speed,
+                })
+                .insert(OrbitHits {
+                    cooldown: orbit.hit_cooldown,
+                    hits: HashMap::new(),
+                })
+                .insert(PlayerSpellMarker)
+                .insert(RigidBody::Kinematic)
+                .insert(Collider::circle(orbit.radius))
+                .insert(Sensor)
+                .insert(CollisionLayers::new(
+                    [Layer::PlayerProjectile],
+                    [Layer::Monster],
+                ))
+                .insert(CollidingEntities::default())
+                .insert(Damage(orbit.damage))
+                .insert(orbit.damage_type)
+                .insert(LifeTime {
+                    timer: Timer::from_seconds(
+                        orbit.duration * duration_multiplier,
+                        TimerMode::Once,
+                    ),
+                })
+                .id();
+            if let Some(knockback) = orbit.knockback {
+                commands.entity(orbit_id).insert(Knockback(knockback));
+            }
+        }
+    }
+}
+
+/// Moves the orbiting projectiles around the player
+fn move_orbits(
+    time: Res<Time>,
+    player: Query<&Transform, (With<PlayerMarker>, Without<Orbit>)>,
+    mut orbits: Query<(&mut Orbit, &mut Transform)>,
+) {
+    let Ok(player_transform) = player.get_single() else {
+        return;
+    };
+    let center = player_transform.translation.truncate();
+    for (mut orbit, mut transform) in orbits.iter_mut() {
+        orbit.angle = (orbit.angle + orbit.speed * time.delta_secs()).rem_euclid(TAU);
+        let position = center + Vec2::from_angle(orbit.angle) * orbit.distance;
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+/// Damages the monsters touching the orbiting projectiles,
+/// once per monster until its hit cooldown finishes
+fn orbit_hits(
+    time: Res<Time>,
+    mut orbits: Query<(
+        Entity,
+        &mut OrbitHits,
+        &CollidingEntities,
+        &Damage,
+        &DamageType,
+    )>,
+    monsters: Query<(), With<MonsterMarker>>,
+    mut ev_damage: EventWriter<DamageEvent>,
+) {
+    for (orbit_id, mut orbit_hits, colliding, damage, damage_type) in orbits.iter_mut() {
+        orbit_hits
+            .hits
+            .retain(|_, timer| !timer.tick(time.delta()).finished());
+        let cooldown = orbit_hits.cooldown;
+        for monster in colliding.iter() {
+            if !monsters.contains(*monster) || orbit_hits.hits.contains_key(monster) {
+                continue;
+            }
+            orbit_hits
+                .hits
+                .insert(*monster, Timer::from_seconds(cooldown, TimerMode::Once));
+            ev_damage.send(DamageEvent {
+                damage: damage.0,
+                target: *monster,
+                source: orbit_id,
+                on_hit: true,
+                damage_type: *damage_type,
+            });
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use ar_core::{
+    ActiveBuffs, BattleSet, MagnetMarker, MaxHealth, MaxStamina, ModifierSource, PlayerMarker,
+    PlayerStats, Stat,
+};
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+/// The largest radius the magnet collider can have
+const MAX_MAGNET_RADIUS: f32 = 128.0;
+
+pub struct PlayerStatsPlugin;
+
+impl Plugin for PlayerStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (sync_buff_modifiers, sync_max_stats, update_magnet_radius)
+                .chain()
+                .in_set(BattleSet),
+        );
+    }
+}
+
+/// Replaces the buff modifiers of the player's stats with the currently active buffs
+#[allow(clippy::type_complexity)]
+fn sync_buff_modifiers(
+    mut player: Query<(&ActiveBuffs, &mut PlayerStats), (With<PlayerMarker>, Changed<ActiveBuffs>)>,
+) {
+    for (buffs, mut stats) in player.iter_mut() {
+        stats.clear_source(ModifierSource::Buff);
+        for buff in buffs.0.iter() {
+            for modifier in buff.modifiers.iter() {
+                if let Some(modifier) = modifier.stat_modifier(buff.stacks) {
+                    stats.add_modifier(modifier);
+                }
+            }
+        }
+    }
+}
+
+/// Keeps MaxHealth and MaxStamina in line with the player's stats
+#[allow(clippy::type_complexity)]
+fn sync_max_stats(
+    mut player: Query<
+        (&PlayerStats, &mut MaxHealth, &mut MaxStamina),
+        (With<PlayerMarker>, Changed<PlayerStats>),
+    >,
+) {
+    for (stats, mut max_health, mut max_stamina) in player.iter_mut() {
+        let health = stats.get(Stat::MaxHealth).round() as usize;
+        if max_health.0 != health {
+            max_health.0 = health;
+        }
+        let stamina = stats.get(Stat::MaxStamina);
+        if max_stamina.0 != stamina {
+            max_stamina.0 = stamina;
+        }
+    }
+}
+
+/// Resizes the magnet collider when the player's magnet stat changes
+fn update_magnet_radius(
+    player: Query<&PlayerStats, With<PlayerMarker>>,
+    mut magnet: Query<&mut Collider, With<MagnetMarker>>,
+    mut current_radius: Local<f32>,
+) {
+    let (Ok(stats), Ok(mut collider)) = (player.get_single(), magnet.get_single_mut()) else {
+        return;
+    };
+    let radius = stats.get(Stat::Magnet).min(MAX_MAGNET_RADIUS);
+    if *current_radius != radius {
+        *current_radius = radius;
+        *collider = Collider::circle(radius);
+    }
+}
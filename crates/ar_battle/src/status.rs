@@ -0,0 +1,158 @@
+use crate::DamageEvent;
+use ar_core::{
+    ApplyStatusEffectEvent, Armor, BattleSet, DamageType, Health, PlayerMarker, PlayerMinusHpEvent,
+    Shield, StatusEffectKind, StatusEffects, Stunned,
+};
+use bevy::prelude::*;
+
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatusEffectEvent>().add_systems(
+            FixedUpdate,
+            (
+                apply_status_effects,
+                tick_status_effects,
+                update_stunned,
+                tint_status_effects,
+            )
+                .chain()
+                .in_set(BattleSet),
+        );
+    }
+}
+
+fn apply_status_effects(
+    mut ev_apply: EventReader<ApplyStatusEffectEvent>,
+    mut effects: Query<&mut StatusEffects>,
+) {
+    for ev in ev_apply.read() {
+        if let Ok(mut effects) = effects.get_mut(ev.target) {
+            effects.apply(&ev.effect, ev.source);
+        }
+    }
+}
+
+/// Ticks the duration of the status effects and deals their damage over time,
+/// the player's damage over time ignores the invulnerability frames
+/// but not the armor and the shield
+fn tick_status_effects(
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &mut StatusEffects,
+        Option<&mut Health>,
+        Has<PlayerMarker>,
+    )>,
+    mut player_defense: Query<(&Armor, &mut Shield), With<PlayerMarker>>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    mut ev_player_damaged: EventWriter<PlayerMinusHpEvent>,
+) {
+    for (entity, mut effects, health, is_player) in query.iter_mut() {
+        if effects.0.is_empty() {
+            continue;
+        }
+        let mut damage = 0;
+        let mut damage_type = DamageType::Physical;
+        let mut source = entity;
+        for effect in effects.0.iter_mut() {
+            effect.duration.tick(time.delta());
+            if effect.tick_damage > 0 && effect.tick.tick(time.delta()).just_finished() {
+                damage += effect.tick_damage * effect.stacks as usize;
+                damage_type = effect.kind.damage_type();
+                source = effect.source;
+            }
+        }
+        effects.0.retain(|effect| !effect.duration.finished());
+        if damage == 0 {
+            continue;
+        }
+        if is_player {
+            let (Some(mut health), Ok((armor, mut shield))) =
+                (health, player_defense.get_single_mut())
+            else {
+                continue;
+            };
+            // the same path as the hits of player_damaged_handler
+            let damage = shield.absorb(armor.mitigate(damage));
+            if damage == 0 {
+                continue;
+            }
+            if health.0 <= damage {
+                health.0 = 0;
+            } else {
+                health.0 -= damage;
+                ev_player_damaged.send(PlayerMinusHpEvent { damage });
+            }
+        } else {
+            ev_damage.send(DamageEvent {
+                damage,
+                target: entity,
+                source,
+                on_hit: false,
+                damage_type,
+            });
+        }
+    }
+}
+
+/// Inserts or removes the Stunned marker, which stops the AI of the entity
+fn update_stunned(
+    mut commands: Commands,
+    query: Query<(Entity, &StatusEffects, Has<Stunned>), Changed<StatusEffects>>,
+) {
+    for (entity, effects, stunned) in query.iter() {
+        match (effects.is_stunned(), stunned) {
+            (true, false) => {
+                commands.entity(entity).insert(Stunned);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Stunned>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The status effect the entity was last tinted with
+#[derive(Component, Default)]
+struct StatusTint(Option<StatusEffectKind>);
+
+/// Tints the entity with the color of its most relevant status effect,
+/// the color is only written when that effect changes so the tints of other systems stay,
+/// and it is only cleared if the sprite still has the tint of the effect
+fn tint_status_effects(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &StatusEffects, &mut Sprite, Option<&mut StatusTint>),
+        Changed<StatusEffects>,
+    >,
+) {
+    for (entity, effects, mut sprite, tint) in query.iter_mut() {
+        let kind = effects.tint_kind();
+        let previous = match tint {
+            Some(mut tint) => {
+                if tint.0 == kind {
+                    continue;
+                }
+                std::mem::replace(&mut tint.0, kind)
+            }
+            None => {
+                if kind.is_none() {
+                    continue;
+                }
+                commands.entity(entity).insert(StatusTint(kind));
+                None
+            }
+        };
+        match kind {
+            Some(kind) => sprite.color = kind.tint(),
+            None => {
+                if previous.is_some_and(|previous| sprite.color == previous.tint()) {
+                    sprite.color = Color::WHITE;
+                }
+            }
+        }
+    }
+}
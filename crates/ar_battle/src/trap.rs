@@ -0,0 +1,182 @@
+use crate::{spell_sprite, DamageEvent, SpellsSheetSmall};
+use ar_core::{
+    ApplyStatusEffectEvent, BattleSet, DamageType, Layer, LifeTime, MonsterMarker, PlayerMarker,
+    PlayerSpellMarker, PlayerStats, Stat, StatusEffectKind, StatusEffectTemplate, TrapPlacement,
+    TrapTrigger,
+};
+use ar_spells::generator::OwnedTrapSpells;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_rand::prelude::WyRand;
+use bevy_rand::resource::GlobalEntropy;
+use rand_core::RngCore;
+use std::f32::consts::TAU;
+
+/// How long the explosion of a trap stays on screen
+const TRAP_EXPLOSION_LIFETIME: f32 = 0.2;
+/// The transparency of a trap that isn't armed yet
+const UNARMED_ALPHA: f32 = 0.4;
+
+pub struct TrapPlugin;
+
+impl Plugin for TrapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (cast_player_traps, trigger_traps).chain().in_set(BattleSet),
+        );
+    }
+}
+
+/// A trap dropped by the player, it is removed once triggered,
+/// the entity is kept until the end of its explosion so it stays the source of the damage
+#[derive(Component)]
+struct Trap {
+    spell: String,
+    arm: Timer,
+    damage: usize,
+    trigger: TrapTrigger,
+}
+
+/// Drops the player's traps when their cooldown finishes,
+/// unless the spell already has its maximum number of active traps
+fn cast_player_traps(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut player: Query<(&mut OwnedTrapSpells, &Transform, &PlayerStats), With<PlayerMarker>>,
+    traps: Query<&Trap>,
+    sprite_sheet: Res<SpellsSheetSmall>,
+) {
+    let Ok((mut owned_traps, player_transform, stats)) = player.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta().div_f32(stats.cooldown());
+    let area = stats.get(Stat::Area);
+    let duration_multiplier = stats.get(Stat::Duration);
+    for trap in owned_traps.spells.iter_mut() {
+        if !trap.cooldown.tick(delta).just_finished() {
+            continue;
+        }
+        let active = traps.iter().filter(|t| t.spell == trap.name).count();
+        if active >= trap.max_active as usize {
+            continue;
+        }
+        let player_position = player_transform.translation.truncate();
+        let position = match trap.placement {
+            TrapPlacement::Player => player_position,
+            TrapPlacement::Random(range) => {
+                let angle = rng.next_u32() as f32 / u32::MAX as f32 * TAU;
+                let distance = rng.next_u32() as f32 / u32::MAX as f32 * range;
+                player_position + Vec2::from_angle(angle) * distance
+            }
+        };
+        let radius = trap.radius * area;
+        let mut sprite = spell_sprite(
+            sprite_sheet.sprite.get(trap.sprite.as_str()),
+            Vec2::splat(radius * 2.0),
+            Color::srgb_u8(180, 140, 90),
+        );
+        sprite.color.set_alpha(UNARMED_ALPHA);
+        commands
+            .spawn(sprite)
+            .insert(Transform::from_translation(position.extend(3.0)))
+            .insert(Trap {
+                spell: trap.name.clone(),
+                arm: Timer::from_seconds(trap.arm_time, TimerMode::Once),
+                damage: trap.damage,
+                trigger: match trap.trigger {
+                    TrapTrigger::Explode(radius) => TrapTrigger::Explode(radius * area),
+                    TrapTrigger::Root(duration) => {
+                        TrapTrigger::Root(duration * duration_multiplier)
+                    }
+                },
+            })
+            .insert(PlayerSpellMarker)
+            .insert(trap.damage_type)
+            .insert(RigidBody::Static)
+            .insert(Collider::circle(radius))
+            .insert(Sensor)
+            .insert(CollisionLayers::new([Layer::PlayerAOE], [Layer::Monster]))
+            .insert(CollidingEntities::default())
+            .insert(LifeTime {
+                timer: Timer::from_seconds(trap.lifetime * duration_multiplier, TimerMode::Once),
+            });
+    }
+}
+
+/// Arms the traps after their delay, then triggers them with the first monster inside,
+/// an explosion damages every monster in its radius, a root only the monster that triggered it
+#[allow(clippy::type_complexity)]
+fn trigger_traps(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut traps: Query<(
+        Entity,
+        &mut Trap,
+        &mut Sprite,
+        &Transform,
+        &CollidingEntities,
+        &DamageType,
+    )>,
+    monsters: Query<(Entity, &Transform), With<MonsterMarker>>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    mut ev_status: EventWriter<ApplyStatusEffectEvent>,
+) {
+    for (trap_id, mut trap, mut sprite, transform, colliding, damage_type) in traps.iter_mut() {
+        if !trap.arm.finished() {
+            if trap.arm.tick(time.delta()).just_finished() {
+                sprite.color.set_alpha(1.0);
+            }
+            continue;
+        }
+        let Some(triggered_by) = colliding.iter().find(|e| monsters.contains(**e)) else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        let targets: Vec<Entity> = match trap.trigger {
+            TrapTrigger::Explode(radius) => {
+                sprite.custom_size = Some(Vec2::splat(radius * 2.0));
+                monsters
+                    .iter()
+                    .filter(|(_, t)| {
+                        t.translation.truncate().distance_squared(position) <= radius * radius
+                    })
+                    .map(|(monster, _)| monster)
+                    .collect()
+            }
+            TrapTrigger::Root(duration) => {
+                ev_status.send(ApplyStatusEffectEvent {
+                    target: *triggered_by,
+                    source: trap_id,
+                    effect: StatusEffectTemplate {
+                        kind: StatusEffectKind::Root,
+                        duration,
+                        tick_damage: None,
+                        tick_rate: None,
+                        slow: None,
+                        max_stacks: None,
+                    },
+                });
+                vec![*triggered_by]
+            }
+        };
+        if trap.damage > 0 {
+            for target in targets {
+                ev_damage.send(DamageEvent {
+                    damage: trap.damage,
+                    target,
+                    source: trap_id,
+                    on_hit: true,
+                    damage_type: *damage_type,
+                });
+            }
+        }
+        commands
+            .entity(trap_id)
+            .remove::<(Trap, Collider, CollidingEntities)>()
+            .insert(LifeTime {
+                timer: Timer::from_seconds(TRAP_EXPLOSION_LIFETIME, TimerMode::Once),
+            });
+    }
+}
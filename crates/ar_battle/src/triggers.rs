@@ -0,0 +1,230 @@
+use crate::chain::{cast_chain, ChainCast};
+use crate::damage::CalculatedDamageEvent;
+use crate::{handle_magnet_collision, AoEDamageMarker, DamageEvent};
+use ar_core::{
+    ActiveBuffs, BattleSet, CardTrigger, CardTriggers, DeathEvent, DropItemEvent, Health,
+    ItemComponent, LifeTime, LootTable, MaxHealth, MonsterMarker, PickupEvent, PlayerMarker,
+    PlayerMinusHpEvent, PlayerProjectileMarker, PlayerSpellMarker, PlayerStats, Stat,
+    TriggerEffect,
+};
+use bevy::prelude::*;
+use bevy_rand::prelude::WyRand;
+use bevy_rand::resource::GlobalEntropy;
+use rand_core::RngCore;
+
+/// How long a nova stays on screen
+const NOVA_LIFETIME: f32 = 0.2;
+/// The fraction of damage lost on each hop of a triggered chain
+const TRIGGER_CHAIN_FALLOFF: f32 = 0.2;
+
+pub struct TriggersPlugin;
+
+impl Plugin for TriggersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (tick_trigger_cooldowns, fire_card_triggers)
+                .chain()
+                .after(handle_magnet_collision)
+                .in_set(BattleSet),
+        );
+    }
+}
+
+/// The damage of a triggered effect, its hits don't fire the on hit and on crit triggers,
+/// so the triggers can't feed themselves
+#[derive(Component)]
+struct TriggeredEffectMarker;
+
+fn tick_trigger_cooldowns(time: Res<Time>, mut triggers: Query<&mut CardTriggers>) {
+    for mut triggers in triggers.iter_mut() {
+        for trigger in triggers.0.iter_mut() {
+            trigger.cooldown.tick(time.delta());
+        }
+    }
+}
+
+/// Fires the player's card triggers from the battle events, each trigger waits for its cooldown
+/// and rolls its chance before its effect happens
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn fire_card_triggers(
+    mut commands: Commands,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut player: Query<
+        (
+            &mut CardTriggers,
+            &mut Health,
+            &MaxHealth,
+            &mut ActiveBuffs,
+            &PlayerStats,
+            &Transform,
+        ),
+        With<PlayerMarker>,
+    >,
+    monsters: Query<(Entity, &Transform), With<MonsterMarker>>,
+    items: Query<&ItemComponent>,
+    player_sources: Query<
+        (),
+        (
+            Or<(
+                With<PlayerProjectileMarker>,
+                With<AoEDamageMarker>,
+                With<PlayerSpellMarker>,
+            )>,
+            Without<TriggeredEffectMarker>,
+        ),
+    >,
+    mut ev_calculated: EventReader<CalculatedDamageEvent>,
+    mut ev_death: EventReader<DeathEvent>,
+    mut ev_player_damaged: EventReader<PlayerMinusHpEvent>,
+    mut ev_pickup: EventReader<PickupEvent>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    mut ev_drop: EventWriter<DropItemEvent>,
+) {
+    let Ok((mut triggers, mut health, max_health, mut active_buffs, stats, player_transform)) =
+        player.get_single_mut()
+    else {
+        return;
+    };
+    if triggers.0.is_empty() {
+        ev_calculated.clear();
+        ev_death.clear();
+        ev_player_damaged.clear();
+        ev_pickup.clear();
+        return;
+    }
+
+    // what happened since the last tick, with the monster it happened to if any
+    // and where it happened
+    let player_position = player_transform.translation.truncate();
+    let mut fired: Vec<(CardTrigger, Option<Entity>, Vec2)> = Vec::new();
+    for ev in ev_calculated.read() {
+        if !ev.on_hit || !player_sources.contains(ev.source) {
+            continue;
+        }
+        let Ok((_, transform)) = monsters.get(ev.target) else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        fired.push((CardTrigger::OnHit, Some(ev.target), position));
+        if ev.crit {
+            fired.push((CardTrigger::OnCrit, Some(ev.target), position));
+        }
+    }
+    // the dead monsters are already despawned, their position comes with the event
+    fired.extend(
+        ev_death
+            .read()
+            .map(|ev| (CardTrigger::OnKill, None, ev.position.truncate())),
+    );
+    fired.extend(
+        ev_player_damaged
+            .read()
+            .map(|_| (CardTrigger::OnDamaged, None, player_position)),
+    );
+    for ev in ev_pickup.read() {
+        if let Ok(item) = items.get(ev.entity) {
+            fired.push((CardTrigger::OnPickup(item.item_type), None, player_position));
+        }
+    }
+
+    for (trigger, target, position) in fired {
+        for active in triggers.0.iter_mut() {
+            if active.template.trigger != trigger || !active.cooldown.finished() {
+                continue;
+            }
+            if let Some(chance) = active.template.chance {
+                let roll = rng.next_u32() as f32 / u32::MAX as f32;
+                if roll >= chance {
+                    continue;
+                }
+            }
+            active.cooldown.reset();
+
+            match &active.template.effect {
+                TriggerEffect::Heal(heal) => {
+                    health.0 = (health.0 + heal).min(max_health.0);
+                }
+                TriggerEffect::Drop(loot_table) => {
+                    ev_drop.send(DropItemEvent {
+                        position: position.extend(0.0),
+                        loot_table: LootTable(*loot_table),
+                    });
+                }
+                TriggerEffect::Nova {
+                    damage,
+                    radius,
+                    damage_type,
+                } => {
+                    let radius = radius * stats.get(Stat::Area);
+                    let nova_id = commands
+                        .spawn(Sprite::from_color(
+                            Color::srgba_u8(255, 200, 120, 120),
+                            Vec2::splat(radius * 2.0),
+                        ))
+                        .insert(Transform::from_translation(position.extend(3.0)))
+                        .insert(PlayerSpellMarker)
+                        .insert(TriggeredEffectMarker)
+                        .insert(*damage_type)
+                        .insert(LifeTime {
+                            timer: Timer::from_seconds(NOVA_LIFETIME, TimerMode::Once),
+                        })
+                        .id();
+                    for (monster, transform) in monsters.iter() {
+                        if transform.translation.truncate().distance_squared(position)
+                            > radius * radius
+                        {
+                            continue;
+                        }
+                        ev_damage.send(DamageEvent {
+                            damage: *damage,
+                            target: monster,
+                            source: nova_id,
+                            on_hit: true,
+                            damage_type: *damage_type,
+                        });
+                    }
+                }
+                TriggerEffect::Chain {
+                    damage,
+                    jumps,
+                    radius,
+                    damage_type,
+                } => {
+                    let segments = cast_chain(
+                        &mut commands,
+                        &monsters,
+                        &mut ev_damage,
+                        ChainCast {
+                            origin: position,
+                            excluded: target,
+                            damage: *damage as f32,
+                            jumps: *jumps,
+                            radius: radius * stats.get(Stat::Area),
+                            falloff: TRIGGER_CHAIN_FALLOFF,
+                            damage_type: *damage_type,
+                            sprite: None,
+                        },
+                    );
+                    for segment in segments {
+                        commands.entity(segment).insert(TriggeredEffectMarker);
+                    }
+                }
+                TriggerEffect::Buff {
+                    name,
+                    modifiers,
+                    duration,
+                    stacking,
+                } => {
+                    active_buffs.apply(
+                        name,
+                        modifiers,
+                        Timer::from_seconds(duration * stats.get(Stat::Duration), TimerMode::Once),
+                        *stacking,
+                        &active.sprite,
+                    );
+                }
+            }
+        }
+    }
+}
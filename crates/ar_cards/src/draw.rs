@@ -0,0 +1,249 @@
+use ar_core::{
+    CardAction, CardDrawRules, CardDraws, CardRarity, CardType, CardsTemplates, ChooseACard,
+    CurrentCards, PlayerExperience, PlayerMarker, PlayerStats, RemainingCardsByType, Stat,
+};
+use ar_template::evolutions::{EvolutionTemplate, EvolutionTemplates};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rand::prelude::WyRand;
+use bevy_rand::resource::GlobalEntropy;
+use rand::prelude::Rng;
+
+/// The number of cards offered on each level up
+pub const CARDS_PER_DRAW: usize = 3;
+
+/// Draws up to `count` different cards from the remaining ones, weighted by their rarity,
+/// the cards in `excluded` and the banished ones can't be drawn,
+/// if a rarity is `guaranteed` the first card is of that rarity or rarer when there is one left
+#[allow(clippy::too_many_arguments)]
+pub fn draw_cards(
+    rng: &mut GlobalEntropy<WyRand>,
+    remaining_cards: &RemainingCardsByType,
+    cards_templates: &CardsTemplates,
+    luck: f32,
+    guaranteed: Option<CardRarity>,
+    excluded: &[String],
+    banished: &HashSet<String>,
+    count: usize,
+) -> Vec<String> {
+    let rules = &cards_templates.draw_rules;
+    let mut candidates: Vec<(&String, CardRarity, f32)> = remaining_cards
+        .spell_cards
+        .iter()
+        .chain(remaining_cards.powerup_cards.iter())
+        .filter(|key| !excluded.contains(key) && !banished.contains(*key))
+        .filter_map(|key| {
+            let template = cards_templates.cards.get(key)?;
+            let weight = card_weight(rules, template.rarity, &template.card_type, luck);
+            (weight > 0.0).then_some((key, template.rarity, weight))
+        })
+        .collect();
+
+    let mut drawn = Vec::new();
+    if let Some(rarity) = guaranteed {
+        if let Some(idx) = pick_weighted(rng, &candidates, |(_, r, _)| *r >= rarity) {
+            drawn.push(candidates.swap_remove(idx).0.clone());
+        }
+    }
+    while drawn.len() < count {
+        let Some(idx) = pick_weighted(rng, &candidates, |_| true) else {
+            break;
+        };
+        drawn.push(candidates.swap_remove(idx).0.clone());
+    }
+    drawn
+}
+
+fn card_weight(rules: &CardDrawRules, rarity: CardRarity, card_type: &CardType, luck: f32) -> f32 {
+    let weight = rules.rarity_weight(rarity, luck);
+    match card_type {
+        CardType::Spell => weight * rules.spell_weight,
+        CardType::Buff => weight,
+    }
+}
+
+/// Returns the index of a random candidate among the ones accepted by the filter
+fn pick_weighted(
+    rng: &mut GlobalEntropy<WyRand>,
+    candidates: &[(&String, CardRarity, f32)],
+    filter: impl Fn(&(&String, CardRarity, f32)) -> bool,
+) -> Option<usize> {
+    let total: f32 = candidates
+        .iter()
+        .filter(|c| filter(c))
+        .map(|(_, _, w)| w)
+        .sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0.0..total);
+    let mut last = None;
+    for (idx, candidate) in candidates.iter().enumerate() {
+        if !filter(candidate) {
+            continue;
+        }
+        if roll < candidate.2 {
+            return Some(idx);
+        }
+        roll -= candidate.2;
+        last = Some(idx);
+    }
+    // rounding errors can leave a bit of the roll at the end
+    last
+}
+
+/// Puts the card of a ready evolution in place of the last drawn card,
+/// or after the drawn cards if there is room left, one evolution per draw,
+/// a banished evolved card is never offered again
+pub fn offer_evolution(
+    drawn: &mut Vec<String>,
+    evolution_templates: &EvolutionTemplates,
+    current_cards: &CurrentCards,
+    cards_templates: &CardsTemplates,
+    banished: &HashSet<String>,
+) {
+    let Some(evolution) = evolution_templates
+        .evolutions
+        .values()
+        .filter(|evolution| !drawn.contains(&evolution.evolved_card))
+        .filter(|evolution| !banished.contains(&evolution.evolved_card))
+        .find(|evolution| is_evolution_ready(evolution, current_cards, cards_templates))
+    else {
+        return;
+    };
+    if drawn.len() >= CARDS_PER_DRAW {
+        drawn.pop();
+    }
+    drawn.push(evolution.evolved_card.clone());
+}
+
+/// The base card is at its max level, the catalyst card is owned
+/// and the evolved card hasn't been picked yet
+fn is_evolution_ready(
+    evolution: &EvolutionTemplate,
+    current_cards: &CurrentCards,
+    cards_templates: &CardsTemplates,
+) -> bool {
+    let level = |card: &String| current_cards.cards.get(card).copied().unwrap_or_default();
+    let base_maxed = cards_templates
+        .cards
+        .get(&evolution.base_card)
+        .is_some_and(|template| level(&evolution.base_card) >= template.max_level);
+    base_maxed && level(&evolution.catalyst_card) > 0 && level(&evolution.evolved_card) == 0
+}
+
+/// The rarities of the drawn cards, to update the pity counters
+pub fn drawn_rarities(drawn: &[String], cards_templates: &CardsTemplates) -> Vec<CardRarity> {
+    drawn
+        .iter()
+        .filter_map(|key| cards_templates.cards.get(key))
+        .map(|template| template.rarity)
+        .collect()
+}
+
+pub fn to_offer(drawn: Vec<String>) -> [Option<String>; CARDS_PER_DRAW] {
+    let mut drawn = drawn.into_iter();
+    std::array::from_fn(|_| drawn.next())
+}
+
+/// Rerolls, skips or banishes the current cards while the player has uses left
+#[allow(clippy::too_many_arguments)]
+pub fn card_actions(
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut ev_card_action: EventReader<CardAction>,
+    mut choose_a_card: ResMut<ChooseACard>,
+    mut remaining_cards: ResMut<RemainingCardsByType>,
+    mut card_draws: ResMut<CardDraws>,
+    cards_templates: Res<CardsTemplates>,
+    evolution_templates: Res<EvolutionTemplates>,
+    current_cards: Res<CurrentCards>,
+    mut player: Query<(&PlayerStats, &mut PlayerExperience), With<PlayerMarker>>,
+) {
+    let Ok((stats, mut experience)) = player.get_single_mut() else {
+        return;
+    };
+    let luck = stats.get(Stat::Luck);
+    for action in ev_card_action.read() {
+        let Some(offer) = choose_a_card.cards.first().cloned() else {
+            continue;
+        };
+        let offered: Vec<String> = offer.iter().flatten().cloned().collect();
+        match *action {
+            CardAction::Reroll => {
+                if card_draws.rerolls == 0 {
+                    info!("No rerolls left");
+                    continue;
+                }
+                card_draws.rerolls -= 1;
+                // a reroll keeps the guarantee of the pity rules, it doesn't count as a level up
+                let guaranteed = card_draws.pity_due(&cards_templates.draw_rules);
+                let mut drawn = draw_cards(
+                    &mut rng,
+                    &remaining_cards,
+                    &cards_templates,
+                    luck,
+                    guaranteed,
+                    &offered,
+                    &card_draws.banished,
+                    CARDS_PER_DRAW,
+                );
+                if drawn.len() < CARDS_PER_DRAW {
+                    // too few cards left for a new offer, the offered ones can come back
+                    let refill = draw_cards(
+                        &mut rng,
+                        &remaining_cards,
+                        &cards_templates,
+                        luck,
+                        None,
+                        &drawn,
+                        &card_draws.banished,
+                        CARDS_PER_DRAW - drawn.len(),
+                    );
+                    drawn.extend(refill);
+                }
+                offer_evolution(
+                    &mut drawn,
+                    &evolution_templates,
+                    &current_cards,
+                    &cards_templates,
+                    &card_draws.banished,
+                );
+                choose_a_card.cards[0] = to_offer(drawn);
+            }
+            CardAction::Skip => {
+                if card_draws.skips == 0 {
+                    info!("No skips left");
+                    continue;
+                }
+                card_draws.skips -= 1;
+                choose_a_card.cards.remove(0);
+                experience.0 += cards_templates.draw_rules.skip_experience;
+            }
+            CardAction::Banish(index) => {
+                let Some(Some(card)) = offer.get(index as usize) else {
+                    continue;
+                };
+                if card_draws.banishes == 0 {
+                    info!("No banishes left");
+                    continue;
+                }
+                card_draws.banishes -= 1;
+                info!("Banished: {}", card);
+                card_draws.banished.insert(card.clone());
+                remaining_cards.spell_cards.retain(|key| key != card);
+                remaining_cards.powerup_cards.retain(|key| key != card);
+                let replacement = draw_cards(
+                    &mut rng,
+                    &remaining_cards,
+                    &cards_templates,
+                    luck,
+                    None,
+                    &offered,
+                    &card_draws.banished,
+                    1,
+                );
+                choose_a_card.cards[0][index as usize] = replacement.into_iter().next();
+            }
+        }
+    }
+}
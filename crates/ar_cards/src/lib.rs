@@ -1,13 +1,18 @@
+mod draw;
+
 use ar_core::{
-    ApplyCard, CardSet, CardType, CardsTemplates, ChooseACard, ChosenCard, LevelUpEvent, MaxHealth,
-    MaxStamina, PlayerMarker, PowerUp, RemainingCardsByType
+    ActiveTrigger, ApplyCard, Armor, AvailableCards, CardAction, CardDraws, CardSet, CardTriggers,
+    CardType, CardsTemplates, ChooseACard, ChosenCard, CurrentCards, Damage, Health, LevelUpEvent,
+    PauseState, PermanentDebuff, PlayerMarker, PlayerStats, PowerUp, RemainingCardsByType, Shield,
+    Stat,
 };
-use ar_spells::generator::{OwnedProjectileSpells, OwnedAOESpells, ProjectileSpells, AOESpells};
+use ar_spells::level::{OwnedSpells, SpellRegistries};
+use ar_template::evolutions::EvolutionTemplates;
+use ar_template::spells::SpellTemplates;
 use bevy::prelude::*;
 use bevy_rand::prelude::WyRand;
 use bevy_rand::resource::GlobalEntropy;
-use rand::prelude::Rng;
-use std::collections::HashSet;
+use draw::{card_actions, draw_cards, drawn_rarities, offer_evolution, to_offer, CARDS_PER_DRAW};
 
 pub struct CardPlugin;
 
@@ -15,181 +20,334 @@ impl Plugin for CardPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ChosenCard>()
             .add_event::<ApplyCard>()
+            .add_event::<CardAction>()
             .init_resource::<ChooseACard>()
             .add_systems(
                 FixedUpdate,
-                (spawn_cards, chosen_card).chain().in_set(CardSet),
+                (
+                    spawn_cards,
+                    card_actions,
+                    reward_empty_offers,
+                    chosen_card,
+                    sync_available_cards,
+                    pause_while_choosing,
+                )
+                    .chain()
+                    .in_set(CardSet),
             );
     }
 }
 
+/// Draws the cards offered on each level up, weighted by their rarity and the player's luck,
+/// a due pity rule guarantees one of the cards is of its rarity,
+/// and a ready evolution is always offered
+#[allow(clippy::too_many_arguments)]
 fn spawn_cards(
     mut rng: ResMut<GlobalEntropy<WyRand>>,
     cards_by_type: Res<RemainingCardsByType>,
+    cards_templates: Res<CardsTemplates>,
+    evolution_templates: Res<EvolutionTemplates>,
+    current_cards: Res<CurrentCards>,
+    mut card_draws: ResMut<CardDraws>,
+    player: Query<&PlayerStats, With<PlayerMarker>>,
     mut player_level: EventReader<LevelUpEvent>,
     mut choose_a_card: ResMut<ChooseACard>,
 ) {
-    let spell_cards_range = cards_by_type.spell_cards.len();
-    let buff_cards_range = cards_by_type.powerup_cards.len();
-
-    let mut cards: [Option<String>; 3];
+    let luck = player
+        .get_single()
+        .map_or(1.0, |stats| stats.get(Stat::Luck));
 
     for level in player_level.read() {
-        let level = level.level;
-
         #[cfg(debug_assertions)]
-        info!("level: {}", level);
+        info!("level: {}", level.level);
 
-        if level % 5 == 0 && spell_cards_range > 0 {
-            let mut cards_index = HashSet::new();
-            while cards_index.len() < 3 && cards_index.len() < spell_cards_range {
-                cards_index.insert(rng.gen_range(0..spell_cards_range));
-            }
-            let cards_index: Vec<usize> = cards_index.into_iter().collect();
-            match cards_index.len() {
-                3 => {
-                    cards = [
-                        Some(cards_by_type.spell_cards[cards_index[0]].clone()),
-                        Some(cards_by_type.spell_cards[cards_index[1]].clone()),
-                        Some(cards_by_type.spell_cards[cards_index[2]].clone()),
-                    ];
-                }
-                2 => {
-                    cards = [
-                        Some(cards_by_type.spell_cards[cards_index[0]].clone()),
-                        Some(cards_by_type.spell_cards[cards_index[1]].clone()),
-                        None,
-                    ];
-                }
-                1 => {
-                    cards = [
-                        Some(cards_by_type.spell_cards[cards_index[0]].clone()),
-                        None,
-                        None,
-                    ];
-                }
-                _ => {
-                    cards = [None, None, None];
-                }
-            }
-        } else {
-            let mut cards_index = HashSet::new();
-            while cards_index.len() < 3 && cards_index.len() < buff_cards_range {
-                cards_index.insert(rng.gen_range(0..buff_cards_range));
-                #[cfg(debug_assertions)]
-                info!(
-                    "cards_index_len: {}, buff_cards_range: {}",
-                    cards_index.len(),
-                    buff_cards_range
-                );
-            }
-            let cards_index: Vec<usize> = cards_index.into_iter().collect();
-            match cards_index.len() {
-                3 => {
-                    cards = [
-                        Some(cards_by_type.powerup_cards[cards_index[0]].clone()),
-                        Some(cards_by_type.powerup_cards[cards_index[1]].clone()),
-                        Some(cards_by_type.powerup_cards[cards_index[2]].clone()),
-                    ];
-                }
-                2 => {
-                    cards = [
-                        Some(cards_by_type.powerup_cards[cards_index[0]].clone()),
-                        Some(cards_by_type.powerup_cards[cards_index[1]].clone()),
-                        None,
-                    ];
-                }
-                1 => {
-                    cards = [
-                        Some(cards_by_type.powerup_cards[cards_index[0]].clone()),
-                        None,
-                        None,
-                    ];
-                }
-                _ => {
-                    cards = [None, None, None];
-                }
-            }
-        }
-        choose_a_card.cards.push(cards)
+        let rules = &cards_templates.draw_rules;
+        let guaranteed = card_draws.pity_due(rules);
+        let mut drawn = draw_cards(
+            &mut rng,
+            &cards_by_type,
+            &cards_templates,
+            luck,
+            guaranteed,
+            &[],
+            &card_draws.banished,
+            CARDS_PER_DRAW,
+        );
+        offer_evolution(
+            &mut drawn,
+            &evolution_templates,
+            &current_cards,
+            &cards_templates,
+            &card_draws.banished,
+        );
+        card_draws.update_pity(rules, &drawn_rarities(&drawn, &cards_templates));
+        choose_a_card.cards.push(to_offer(drawn));
     }
 }
 
-/// Removes the current available cards from ChooseACard resource and applies the effects
-/// of the chosen card to the player
-#[allow(clippy::too_many_arguments)]
+/// Removes the first offer from ChooseACard resource and applies the effects
+/// of the chosen card to the player,
+/// picking a card again levels it up, at its max level the max level bonus is applied
+/// and the card is removed from the remaining cards
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn chosen_card(
-    mut player: Query<(&mut MaxHealth, &mut MaxStamina), With<PlayerMarker>>,
+    mut player: Query<
+        (
+            &mut PlayerStats,
+            &mut Health,
+            &mut Armor,
+            &mut Shield,
+            &mut Damage,
+            &mut CardTriggers,
+            OwnedSpells,
+        ),
+        With<PlayerMarker>,
+    >,
     mut choose_a_card: ResMut<ChooseACard>,
     mut ev_chosen_card: EventReader<ApplyCard>,
     mut remaining_cards: ResMut<RemainingCardsByType>,
+    mut current_cards: ResMut<CurrentCards>,
     cards_templates: Res<CardsTemplates>,
-    aoe_list: Res<AOESpells>,
-    proj_list: Res<ProjectileSpells>,
-    mut owned_aoe: Single<&mut OwnedAOESpells>,
-    mut owned_projectiles: Single<&mut OwnedProjectileSpells>,
+    spell_templates: Res<SpellTemplates>,
+    evolution_templates: Res<EvolutionTemplates>,
+    registries: SpellRegistries,
 ) {
-    let (mut player_health, mut player_stamina) = player.single_mut();
+    let (
+        mut player_stats,
+        mut player_health,
+        mut player_armor,
+        mut player_shield,
+        mut player_damage,
+        mut player_triggers,
+        mut owned_spells,
+    ) = player.single_mut();
 
     for card in ev_chosen_card.read() {
-        choose_a_card.cards.clear();
+        // the offer may have been rerolled or skipped since the card was chosen
+        let offered = choose_a_card
+            .cards
+            .first()
+            .is_some_and(|offer| offer.iter().flatten().any(|key| *key == card.card));
+        if !offered {
+            continue;
+        }
+        choose_a_card.cards.remove(0);
         let card_template = cards_templates
             .cards
             .get(card.card.as_str())
             .unwrap_or_else(|| panic!("Card doesn't exist: {:?}", card.card));
+        let level = current_cards.cards.entry(card.card.clone()).or_insert(0);
+        *level += 1;
+        let level = *level;
         match card_template.card_type {
             CardType::Spell => {
                 if let Some(spell) = &card_template.spell {
-                    if let Some(aoe_spell) = aoe_list.aoe_spells.get(spell) {
-                        if !owned_aoe.spells.contains(aoe_spell) {
-                            owned_aoe.spells.push(aoe_spell.clone());
-                            if let Some(idx) = remaining_cards.spell_cards.iter().position(|v| *v == aoe_spell.name) {
-                                remaining_cards.spell_cards.swap_remove(idx);
-                            }
-
-                        }
-                    } else if let Some(proj_spell) = proj_list.projectile_spells.get(spell) {
-                        if !owned_projectiles.spells.contains(proj_spell) {
-                            owned_projectiles.spells.push(proj_spell.clone());
-                            if let Some(idx) = remaining_cards.spell_cards.iter().position(|v| *v == proj_spell.name) {
-                                remaining_cards.spell_cards.swap_remove(idx);
-                            }
+                    if owned_spells.learn(&registries, spell) {
+                        // an evolved spell replaces the spell it evolved from
+                        if let Some(base_spell) = evolution_templates
+                            .evolution_of(&card.card)
+                            .and_then(|evolution| cards_templates.cards.get(&evolution.base_card))
+                            .and_then(|base_card| base_card.spell.as_ref())
+                        {
+                            info!("{} evolved into {}", base_spell, spell);
+                            owned_spells.forget(base_spell);
                         }
+                    } else if let Some(delta) = spell_templates
+                        .spells
+                        .get(spell)
+                        .and_then(|template| template.level_up.as_ref())
+                    {
+                        owned_spells.level_up(spell, delta);
                     }
                 }
             }
             CardType::Buff => {
                 if let Some(power_up) = &card_template.upgrade {
-                    match power_up {
-                        PowerUp::HealthUp(health) => {
-                            info!("Health: {}", health);
-                            player_health.0 += *health as usize;
-                        }
-                        PowerUp::AttackUp(attack) => {
-                            info!("Attack: {}", attack);
-                        }
-                        PowerUp::ShieldUp(shield) => {
-                            info!("Shield: {}", shield);
-                        }
-                        PowerUp::SpeedUp(speed) => {
-                            info!("Speed: {}", speed);
-                        }
-                        PowerUp::LootUp(loot) => {
-                            info!("Loot: {}", loot);
-                        }
-                        PowerUp::DamageUp(damage) => {
-                            info!("Damage: {}", damage);
-                        }
-                        PowerUp::ExpUp(exp) => {
-                            info!("Exp: {}", exp);
-                        }
-                        PowerUp::StaminaUp(stamina) => {
-                            info!("Stamina: {}", stamina);
-                            player_stamina.0 += *stamina as f32;
-                        }
-                    }
+                    apply_power_up(
+                        power_up,
+                        &mut player_stats,
+                        &mut player_health,
+                        &mut player_armor,
+                        &mut player_shield,
+                        &mut player_damage,
+                    );
                 }
             }
         }
+        // the triggers are granted once, leveling the card up doesn't stack them
+        if level == 1 {
+            if let Some(triggers) = &card_template.triggers {
+                player_triggers.0.extend(triggers.iter().map(|trigger| {
+                    ActiveTrigger::new(card_template.sprite.clone(), trigger.clone())
+                }));
+            }
+        }
+        // a cursed card takes its toll on every pick
+        if let Some(debuff) = &card_template.debuff {
+            apply_debuff(
+                debuff,
+                &mut player_stats,
+                &mut player_health,
+                &mut player_damage,
+            );
+        }
+
+        if level >= card_template.max_level {
+            info!("{} reached its max level", card_template.name);
+            if let Some(bonus) = &card_template.max_level_bonus {
+                apply_power_up(
+                    bonus,
+                    &mut player_stats,
+                    &mut player_health,
+                    &mut player_armor,
+                    &mut player_shield,
+                    &mut player_damage,
+                );
+            }
+            let cards = match card_template.card_type {
+                CardType::Spell => &mut remaining_cards.spell_cards,
+                CardType::Buff => &mut remaining_cards.powerup_cards,
+            };
+            if let Some(idx) = cards.iter().position(|v| *v == card.card) {
+                cards.swap_remove(idx);
+            }
+        }
+    }
+}
+
+/// Consumes the offers without any card, the player gets the empty offer reward for each one
+#[allow(clippy::type_complexity)]
+fn reward_empty_offers(
+    mut player: Query<
+        (
+            &mut PlayerStats,
+            &mut Health,
+            &mut Armor,
+            &mut Shield,
+            &mut Damage,
+        ),
+        With<PlayerMarker>,
+    >,
+    mut choose_a_card: ResMut<ChooseACard>,
+    cards_templates: Res<CardsTemplates>,
+) {
+    let is_empty = |offer: &[Option<String>; CARDS_PER_DRAW]| offer.iter().all(Option::is_none);
+    // checked first so the resource isn't marked as changed every tick
+    if !choose_a_card.cards.iter().any(is_empty) {
+        return;
+    }
+    let pending = choose_a_card.cards.len();
+    choose_a_card.cards.retain(|offer| !is_empty(offer));
+    let empty_offers = pending - choose_a_card.cards.len();
+    info!("{} offers without cards", empty_offers);
+
+    let Some(reward) = &cards_templates.draw_rules.empty_offer_reward else {
+        return;
+    };
+    let Ok((
+        mut player_stats,
+        mut player_health,
+        mut player_armor,
+        mut player_shield,
+        mut player_damage,
+    )) = player.get_single_mut()
+    else {
+        return;
+    };
+    for _ in 0..empty_offers {
+        apply_power_up(
+            reward,
+            &mut player_stats,
+            &mut player_health,
+            &mut player_armor,
+            &mut player_shield,
+            &mut player_damage,
+        );
+    }
+}
+
+/// Keeps AvailableCards equal to the number of offers waiting to be chosen
+fn sync_available_cards(
+    choose_a_card: Res<ChooseACard>,
+    mut available_cards: ResMut<AvailableCards>,
+) {
+    let pending = choose_a_card.cards.len().min(u8::MAX as usize) as u8;
+    if available_cards.0 != pending {
+        available_cards.0 = pending;
+    }
+}
+
+/// Pauses the game while there is an offer to choose from, and resumes it once they are all
+/// consumed, the other kinds of pause are left alone
+fn pause_while_choosing(
+    available_cards: Res<AvailableCards>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    match (available_cards.0 > 0, pause_state.get()) {
+        (true, PauseState::NotPaused) => next_pause_state.set(PauseState::PowerUp),
+        (false, PauseState::PowerUp) => next_pause_state.set(PauseState::NotPaused),
+        _ => {}
+    }
+}
+
+/// Applies the power up of a card, or of its max level bonus, to the player
+fn apply_power_up(
+    power_up: &PowerUp,
+    player_stats: &mut PlayerStats,
+    player_health: &mut Health,
+    player_armor: &mut Armor,
+    player_shield: &mut Shield,
+    player_damage: &mut Damage,
+) {
+    info!("Power up: {:?}", power_up);
+    if let Some(modifier) = power_up.stat_modifier() {
+        player_stats.add_modifier(modifier);
+    }
+    match power_up {
+        PowerUp::HealthUp(health) => {
+            // the max health is synced from the stats, the current health is raised here
+            player_health.0 += *health as usize;
+        }
+        PowerUp::AttackUp(attack) => {
+            player_damage.0 += *attack as usize;
+        }
+        PowerUp::ShieldUp(shield) => {
+            player_shield.max += *shield as f32;
+            player_shield.current += *shield as f32;
+        }
+        PowerUp::ArmorUp(armor) => {
+            player_armor.flat += *armor as usize;
+        }
+        PowerUp::SpeedUp(_)
+        | PowerUp::LootUp(_)
+        | PowerUp::DamageUp(_)
+        | PowerUp::ExpUp(_)
+        | PowerUp::StaminaUp(_) => {}
+    }
+}
+
+/// Applies the permanent drawback of a cursed card to the player
+fn apply_debuff(
+    debuff: &PermanentDebuff,
+    player_stats: &mut PlayerStats,
+    player_health: &mut Health,
+    player_damage: &mut Damage,
+) {
+    info!("Debuff: {:?}", debuff);
+    if let Some(modifier) = debuff.stat_modifier() {
+        player_stats.add_modifier(modifier);
+    }
+    match debuff {
+        PermanentDebuff::HealthDown(health) => {
+            // a curse can't kill the player
+            player_health.0 = player_health.0.saturating_sub(*health as usize).max(1);
+        }
+        PermanentDebuff::AttackDown(attack) => {
+            player_damage.0 = player_damage.0.saturating_sub(*attack as usize);
+        }
+        PermanentDebuff::StaminaDown(_)
+        | PermanentDebuff::ExpDown(_)
+        | PermanentDebuff::SpeedDown(_) => {}
     }
 }
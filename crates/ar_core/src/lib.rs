@@ -43,7 +43,7 @@ pub enum AppState {
 /// Paused is a manual pause of the game, (not implemented)
 /// Menu is in the pause menu (not implemented)
 /// Shop is triggered by a shop NPC (not implemented)
-/// PowerUp is the selection of a power up after reaching a new level (not implemented)
+/// PowerUp is the selection of a power up after reaching a new level
 /// MetaUpgrades is the buying of meta upgrades after dying (not implemented)
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum PauseState {
@@ -64,6 +64,7 @@ pub enum ItemType {
     Diamond,
     ExperienceOrb,
     Booster,
+    Heal, // Heals the player by its value
 }
 
 #[derive(Component, Debug)]
@@ -132,6 +133,8 @@ pub struct CardSet;
 #[derive(Component)]
 pub struct BaseSpeed(pub f32);
 
+/// Skips the AI of the entity and prevents it from moving,
+/// inserted and removed by the status effect systems
 #[derive(Component)]
 pub struct Stunned;
 
@@ -164,16 +167,127 @@ pub struct CollidedHash(pub HashSet<Entity>);
 #[derive(Component)]
 pub struct Penetration(pub u8);
 
+/// The number of times the projectile can reflect off a wall before despawning,
+/// a projectile without it is despawned when it hits a wall
 #[derive(Component)]
-pub struct UiMarker;
+pub struct Bounce(pub u8);
 
-/// Armor reduces damage taken
+/// The number of times the projectile retargets the nearest monster it hasn't hit yet,
+/// a ricochet doesn't consume the projectile's penetration
 #[derive(Component)]
-pub struct Armor;
+pub struct Ricochet(pub u8);
+
+/// Steers the projectile towards the nearest monster it hasn't hit yet
+#[derive(Component, Clone, Copy, Debug, Deserialize)]
+pub struct Homing {
+    pub turn_rate: f32, // Radians per second
+    pub radius: f32,    // The range in which the projectile looks for a target
+}
 
-/// Shield prevents damage
+/// A marker for the walls of the arena
 #[derive(Component)]
-pub struct Shield;
+pub struct WallMarker;
+
+#[derive(Component)]
+pub struct UiMarker;
+
+/// Armor reduces damage taken,
+/// the flat reduction is applied before the percentage one
+#[derive(Component, Clone, Debug, Default, Deserialize)]
+pub struct Armor {
+    pub flat: usize,
+    pub percent: f32, // 0.0 - 0.9
+}
+
+impl Armor {
+    /// Returns the damage left after the armor's reduction,
+    /// a hit always deals at least 1 damage
+    pub fn mitigate(&self, damage: usize) -> usize {
+        if damage == 0 {
+            return 0;
+        }
+        let damage = damage.saturating_sub(self.flat) as f32;
+        let damage = (damage * (1.0 - self.percent.clamp(0.0, 0.9))).round() as usize;
+        damage.max(1)
+    }
+}
+
+/// The element of a hit, checked against the target's resistances
+#[derive(Component, Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Ice,
+    Poison,
+}
+
+/// The fraction of each damage type that is ignored by the entity,
+/// a negative value makes the entity weak to that type
+#[derive(Component, Clone, Debug, Default, Deserialize)]
+pub struct Resistances {
+    #[serde(default)]
+    pub physical: f32,
+    #[serde(default)]
+    pub fire: f32,
+    #[serde(default)]
+    pub ice: f32,
+    #[serde(default)]
+    pub poison: f32,
+}
+
+impl Resistances {
+    /// Returns the multiplier for the damage of the given type
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        let resistance = match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Fire => self.fire,
+            DamageType::Ice => self.ice,
+            DamageType::Poison => self.poison,
+        };
+        1.0 - resistance.clamp(-1.0, 1.0)
+    }
+}
+
+/// Shield prevents damage, absorbing it until depleted,
+/// it regenerates after the entity hasn't taken damage for a while
+#[derive(Component, Debug)]
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+    pub regen: f32, // Per second
+    pub regen_delay: Timer,
+}
+
+impl Shield {
+    pub fn new(max: f32, regen: f32, regen_delay: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            regen,
+            regen_delay: Timer::from_seconds(regen_delay, TimerMode::Once),
+        }
+    }
+
+    /// Absorbs as much of the damage as possible, returning the damage left
+    pub fn absorb(&mut self, damage: usize) -> usize {
+        if damage == 0 {
+            return 0;
+        }
+        self.regen_delay.reset();
+        let absorbed = self.current.min(damage as f32).floor();
+        self.current -= absorbed;
+        damage - absorbed as usize
+    }
+}
+
+/// The shield of a monster template
+#[derive(Clone, Debug, Deserialize)]
+pub struct ShieldTemplate {
+    pub max: f32,
+    pub regen: f32,
+    pub regen_delay: f32,
+}
 
 #[derive(Component)]
 pub struct Cooldown(pub Timer);
@@ -209,12 +323,13 @@ pub struct PlayerMinusHpEvent {
 #[derive(Debug, Event)]
 pub struct PlayerDirection(pub Vec2);
 
-/// Boosts the movespeed of the player
+/// Boosts the movespeed of the player while held,
+/// draining stamina continuously
 #[derive(Debug, Event)]
 pub struct BoostUsage(pub bool);
 
 /// Used for moving the player in a dash event,
-/// not implemented
+/// the dash is a short burst of fixed distance in the last direction the player faced
 #[derive(Debug, Event)]
 pub struct DashUsage(pub bool);
 
@@ -261,6 +376,14 @@ pub struct ApplyCard {
     pub card: String,
 }
 
+/// The actions the player can take on the current cards instead of choosing one
+#[derive(Debug, Event, Clone, Copy, PartialEq)]
+pub enum CardAction {
+    Reroll,     // Draws new cards
+    Skip,       // Discards the cards for some experience
+    Banish(u8), // Removes the card from the remaining cards for the run and replaces it
+}
+
 /// Changes the camera following strategy of the game
 /// Rect is the default, adjusts itself only when the player moves out of the current 'rect'
 /// Player moves the camera to follow the player each frame
@@ -282,6 +405,8 @@ pub struct ChangeBackgroundEvent;
 #[derive(Debug, Event)]
 pub struct DeathEvent {
     pub target: Entity,
+    // Where the target died, it may be despawned by the time the event is read
+    pub position: Vec3,
 }
 
 /// Displays the damage that happened on the ground,
@@ -290,6 +415,8 @@ pub struct DeathEvent {
 pub struct DisplayDamageEvent {
     pub damage: usize,
     pub target: Entity,
+    pub crit: bool,
+    pub damage_type: DamageType,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -308,6 +435,11 @@ pub enum SpellType {
     Swing,
     Buff,
     AoE,
+    Chain,
+    Beam,
+    Orbit,
+    Trap,
+    Aura,
 }
 
 /// Reward is automatically given when a condition is met
@@ -374,9 +506,20 @@ pub struct BuffSpellMarker;
 #[derive(Component)]
 pub struct PlayerProjectileMarker;
 
+/// A marker for the entities that deal the player's damage other than projectiles and aoes,
+/// their hits are scaled by the player's stats like the projectiles' ones
+#[derive(Component)]
+pub struct PlayerSpellMarker;
+
 #[derive(Component)]
 pub struct MonsterProjectileMarker;
 
+/// An area that damages the player while it stays inside, i.e., a pool of poison,
+/// it needs a Damage, a Sensor collider and the Hazard layer,
+/// the damage is applied again every time the player's invulnerability frames end
+#[derive(Component)]
+pub struct DamageZone;
+
 #[derive(Component)]
 pub struct MagnetMarker;
 
@@ -393,13 +536,7 @@ pub enum Layer {
     Item,
     Magnet,
     Wall,
-}
-
-#[derive(Resource, Debug)]
-pub struct PickupRadius {
-    pub default_radius: f32,
-    pub multiplier: f32,
-    pub max_radius: f32,
+    Hazard,
 }
 
 /// Keeps the internal score of the game,
@@ -425,6 +562,7 @@ pub struct PlayerExperience(pub usize);
 #[derive(Component)]
 pub struct PlayerLevel(pub u8);
 
+/// The number of offers waiting in ChooseACard, kept in sync by the cards systems
 #[derive(Resource)]
 pub struct AvailableCards(pub u8);
 
@@ -434,12 +572,29 @@ pub struct PlayerLastDirection {
     pub direction: Vec2,
 }
 
-/// Defines the cooldown of the dash and if the player is dashing
+/// Defines the cooldown of the dash and if the player is dashing,
+/// the timer must be set on Once, the player can dash when it is finished
 #[derive(Resource)]
 pub struct PlayerDash {
     pub timer: Timer,
-    pub cooldown_reduction: f32, // 0.0 - 1.0
     pub dashing: bool,
+    pub distance: f32,
+    pub duration: f32,
+    pub stamina_cost: f32,
+    // Whether the player ignores the monsters while dashing
+    pub pass_through: bool,
+}
+
+/// Added to the player for the duration of the dash,
+/// the player is invulnerable and its input is ignored while dashing
+#[derive(Component)]
+pub struct Dashing {
+    pub direction: Vec2,
+    pub timer: Timer,
+    // The interval between the afterimages of the trail
+    pub trail: Timer,
+    // The player's collision layers before the dash, restored when it ends
+    pub layers: CollisionLayers,
 }
 
 /// The max stamina the entity can have
@@ -469,6 +624,202 @@ pub struct SpellAOE {
     pub aoe_pattern: SpellAOEType,
     pub aoe_knockback: Option<f32>,
     pub aoe_distributed: bool, // whether the total damage is distributed among the targets
+    // If it is of type None then the aoe is anchored to the player
+    pub aoe_anchor: Option<AoEAnchor>,
+    // If it is of type None then the aoe only hits once
+    pub aoe_duration: Option<f32>,
+    // The interval between hits of an aoe with a duration
+    pub aoe_tick_rate: Option<f32>,
+    pub aoe_effect: Option<StatusEffectTemplate>,
+    // If it is of type None then the aoe deals physical damage
+    pub aoe_damage_type: Option<DamageType>,
+}
+
+/// Where the aoe is spawned
+#[derive(Clone, Copy, Deserialize, Debug, Default)]
+pub enum AoEAnchor {
+    #[default]
+    Player,
+    // The distance from the player, in the direction the player was last facing
+    AimPoint(f32),
+    // The range around the player to look for a monster, falls back to the player
+    RandomMonster(f32),
+}
+
+/// Where the trap is dropped
+#[derive(Clone, Copy, Deserialize, Debug, Default)]
+pub enum TrapPlacement {
+    #[default]
+    Player,
+    // The range around the player of the random point
+    Random(f32),
+}
+
+/// What the trap does when a monster enters it
+#[derive(Clone, Copy, Deserialize, Debug)]
+pub enum TrapTrigger {
+    // Damages every monster within the radius
+    Explode(f32),
+    // Damages and roots the monster for the duration
+    Root(f32),
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum StatusEffectKind {
+    Burn,
+    Poison,
+    Slow,
+    Freeze,
+    Stun,
+    // The entity can't move but can still act
+    Root,
+}
+
+impl StatusEffectKind {
+    /// The damage type of the damage over time dealt by the effect
+    pub fn damage_type(&self) -> DamageType {
+        match self {
+            StatusEffectKind::Burn => DamageType::Fire,
+            StatusEffectKind::Poison => DamageType::Poison,
+            StatusEffectKind::Freeze => DamageType::Ice,
+            StatusEffectKind::Slow | StatusEffectKind::Stun | StatusEffectKind::Root => {
+                DamageType::Physical
+            }
+        }
+    }
+
+    /// The tint of an entity under the effect
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusEffectKind::Freeze => Color::srgb_u8(120, 180, 255),
+            StatusEffectKind::Stun => Color::srgb_u8(255, 240, 120),
+            StatusEffectKind::Burn => Color::srgb_u8(255, 140, 60),
+            StatusEffectKind::Poison => Color::srgb_u8(120, 220, 90),
+            StatusEffectKind::Root => Color::srgb_u8(160, 120, 80),
+            StatusEffectKind::Slow => Color::srgb_u8(170, 200, 230),
+        }
+    }
+}
+
+/// The template of a status effect,
+/// used by spells and monster attacks to apply effects on hit
+#[derive(Clone, Deserialize, Debug)]
+pub struct StatusEffectTemplate {
+    pub kind: StatusEffectKind,
+    pub duration: f32,
+    // The damage dealt every tick, per stack
+    pub tick_damage: Option<usize>,
+    pub tick_rate: Option<f32>,
+    // The fraction of the speed that is removed, per stack, 0.3 is 30% slower
+    pub slow: Option<f32>,
+    // If it is of type None then the effect doesn't stack, only refreshes its duration
+    pub max_stacks: Option<u8>,
+}
+
+/// A status effect currently applied to the entity
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub duration: Timer,
+    pub tick: Timer,
+    pub tick_damage: usize,
+    pub slow: f32,
+    pub stacks: u8,
+    pub max_stacks: u8,
+    pub source: Entity,
+}
+
+/// The status effects currently applied to the entity,
+/// there is at most one effect of each kind, reapplying it refreshes
+/// its duration and adds a stack up to the limit
+#[derive(Component, Debug, Default)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn apply(&mut self, template: &StatusEffectTemplate, source: Entity) {
+        if let Some(effect) = self.0.iter_mut().find(|e| e.kind == template.kind) {
+            effect.duration = Timer::from_seconds(template.duration, TimerMode::Once);
+            effect.stacks = (effect.stacks + 1).min(effect.max_stacks);
+            effect.source = source;
+            return;
+        }
+        self.0.push(StatusEffect {
+            kind: template.kind,
+            duration: Timer::from_seconds(template.duration, TimerMode::Once),
+            tick: Timer::from_seconds(template.tick_rate.unwrap_or(1.0), TimerMode::Repeating),
+            tick_damage: template.tick_damage.unwrap_or(0),
+            slow: template.slow.unwrap_or(0.0),
+            stacks: 1,
+            max_stacks: template.max_stacks.unwrap_or(1).max(1),
+            source,
+        });
+    }
+
+    /// Whether the entity can't move or act
+    pub fn is_stunned(&self) -> bool {
+        self.0
+            .iter()
+            .any(|e| matches!(e.kind, StatusEffectKind::Stun | StatusEffectKind::Freeze))
+    }
+
+    /// Whether the entity can't move, but can still act
+    pub fn is_rooted(&self) -> bool {
+        self.0.iter().any(|e| e.kind == StatusEffectKind::Root)
+    }
+
+    /// The most relevant effect, the one the entity is tinted with
+    pub fn tint_kind(&self) -> Option<StatusEffectKind> {
+        [
+            StatusEffectKind::Freeze,
+            StatusEffectKind::Stun,
+            StatusEffectKind::Burn,
+            StatusEffectKind::Poison,
+            StatusEffectKind::Root,
+            StatusEffectKind::Slow,
+        ]
+        .into_iter()
+        .find(|kind| self.0.iter().any(|e| e.kind == *kind))
+    }
+
+    /// The multiplier applied to the entity's velocity
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_stunned() || self.is_rooted() {
+            return 0.0;
+        }
+        self.0
+            .iter()
+            .filter(|e| e.kind == StatusEffectKind::Slow)
+            .fold(1.0, |speed, e| {
+                speed * (1.0 - e.slow * e.stacks as f32).max(0.0)
+            })
+    }
+}
+
+/// Applies a status effect to the target hit by the entity
+#[derive(Component, Clone, Debug)]
+pub struct OnHitEffect(pub StatusEffectTemplate);
+
+/// An event to apply a status effect to the target
+#[derive(Debug, Event)]
+pub struct ApplyStatusEffectEvent {
+    pub target: Entity,
+    pub source: Entity,
+    pub effect: StatusEffectTemplate,
+}
+
+/// Pushes the target away from the source on hit,
+/// the value is the impulse applied, so heavier targets are pushed less
+#[derive(Component, Debug)]
+pub struct Knockback(pub f32);
+
+/// The fraction of the knockback impulse that is ignored, 0.0 - 1.0
+#[derive(Component, Debug)]
+pub struct KnockbackResistance(pub f32);
+
+/// Prevents the AI from overwriting the entity's velocity after a knockback
+#[derive(Component, Debug)]
+pub struct Staggered {
+    pub timer: Timer,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -500,8 +851,17 @@ pub struct SpellProjectileType {
     pub projectile_radius: f32, // The size of the collider
     pub projectile_mass: f32,   // The mass of the collider
     pub projectile_lifetime: f32,
-    pub projectile_bounce: Option<bool>,
+    // The number of times the projectile reflects off walls
+    pub projectile_bounce: Option<u8>,
+    // The number of times the projectile retargets another monster after a hit
+    pub projectile_ricochet: Option<u8>,
+    // If it is of type None then the projectile flies straight
+    pub projectile_homing: Option<Homing>,
     pub projectile_penetration: Option<u8>,
+    pub projectile_knockback: Option<f32>,
+    pub projectile_effect: Option<StatusEffectTemplate>,
+    // If it is of type None then the projectile deals physical damage
+    pub projectile_damage_type: Option<DamageType>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -527,9 +887,312 @@ pub struct SpellSwingType {
     pub swing_length: f32,
 }
 
+/// A spell that hits the nearest monster, then jumps to the nearest monster it hasn't hit
+#[derive(Clone, Deserialize, Debug)]
+pub struct SpellChainType {
+    pub chain_sprite: String,
+    pub chain_damage: usize,
+    pub chain_jumps: u8,    // The number of hops after the first hit
+    pub chain_radius: f32,  // The range of each hop, and of the first hit from the player
+    pub chain_falloff: f32, // The fraction of the damage lost on each hop, 0.0 - 1.0
+    // If it is of type None then the chain deals physical damage
+    pub chain_damage_type: Option<DamageType>,
+}
+
+/// A spell that channels a ray from the player along the aim direction,
+/// it is stopped by the walls
+#[derive(Clone, Deserialize, Debug)]
+pub struct SpellBeamType {
+    pub beam_sprite: String,
+    pub beam_damage: usize,
+    pub beam_length: f32,
+    // Only the width of the sprite, the hits are along a ray
+    pub beam_width: f32,
+    // How long the beam is channeled, and the interval between its hits
+    pub beam_duration: f32,
+    pub beam_tick_rate: f32,
+    // If it is true then the beam hits every monster along its length, otherwise only the first
+    pub beam_pierce: bool,
+    // If it is of type None then the beam deals physical damage
+    pub beam_damage_type: Option<DamageType>,
+}
+
+/// A spell whose projectiles circle the player for a duration
+#[derive(Clone, Deserialize, Debug)]
+pub struct SpellOrbitType {
+    pub orbit_sprite: String,
+    pub orbit_count: u8,
+    pub orbit_damage: usize,
+    pub orbit_distance: f32, // The radius of the circle around the player
+    pub orbit_speed: f32,    // The angular speed, in radians per second
+    pub orbit_radius: f32,   // The size of the collider
+    pub orbit_duration: f32,
+    // How long a projectile waits before it can hit the same monster again
+    pub orbit_hit_cooldown: f32,
+    pub orbit_knockback: Option<f32>,
+    // If it is of type None then the projectiles deal physical damage
+    pub orbit_damage_type: Option<DamageType>,
+}
+
+/// A spell that drops stationary traps, triggered by the first monster that enters them
+#[derive(Clone, Deserialize, Debug)]
+pub struct SpellTrapType {
+    pub trap_sprite: String,
+    pub trap_damage: usize,
+    pub trap_radius: f32,   // The size of the sensor that triggers the trap
+    pub trap_arm_time: f32, // The delay before the trap can be triggered
+    pub trap_lifetime: f32,
+    pub trap_max_active: u8,
+    pub trap_trigger: TrapTrigger,
+    // If it is of type None then the trap is dropped at the player's position
+    pub trap_placement: Option<TrapPlacement>,
+    // If it is of type None then the trap deals physical damage
+    pub trap_damage_type: Option<DamageType>,
+}
+
+/// A spell that stays around the player as long as it is owned,
+/// the cooldown of the spell is the interval between its hits
+#[derive(Clone, Deserialize, Debug)]
+pub struct SpellAuraType {
+    pub aura_sprite: String,
+    pub aura_damage: usize,
+    pub aura_radius: f32,
+    pub aura_effect: Option<StatusEffectTemplate>,
+    // If it is of type None then the aura deals physical damage
+    pub aura_damage_type: Option<DamageType>,
+}
+
+/// What a spell gains each time its card is picked again,
+/// the deltas that don't apply to the type of the spell are ignored
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct SpellLevelUp {
+    pub damage: Option<usize>,
+    // The projectiles of projectiles and orbits, the jumps of chains, the max active traps
+    pub count: Option<u8>,
+    // The fraction of the cooldown removed, 0.1 is 10% shorter
+    pub cooldown: Option<f32>,
+    // The fraction added to the size of the spell, 0.1 is 10% bigger
+    pub area: Option<f32>,
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct SpellBuffType {
     pub buff_duration: f32,
+    pub buff_sprite: String,
+    pub buff_modifiers: Vec<BuffModifier>,
+    // If it is of type None then casting the buff again refreshes its duration
+    pub buff_stacking: Option<BuffStacking>,
+}
+
+/// A temporary modifier granted by a buff spell,
+/// the multipliers are added to the base, i.e., MoveSpeed(0.5) is +50% move speed
+#[derive(Clone, Deserialize, Debug)]
+pub enum BuffModifier {
+    MoveSpeed(f32),
+    Damage(f32),
+    ProjectileCount(u8),
+    PickupRadius(f32),
+    Invulnerability,
+}
+
+impl BuffModifier {
+    /// The modifier to the player's stats granted by the buff with the given stacks,
+    /// Invulnerability isn't a stat
+    pub fn stat_modifier(&self, stacks: u8) -> Option<StatModifier> {
+        let stacks = stacks as f32;
+        let (stat, kind) = match self {
+            BuffModifier::MoveSpeed(x) => {
+                (Stat::MoveSpeed, ModifierKind::Multiplicative(x * stacks))
+            }
+            BuffModifier::Damage(x) => (Stat::Might, ModifierKind::Multiplicative(x * stacks)),
+            BuffModifier::ProjectileCount(x) => {
+                (Stat::Amount, ModifierKind::Additive(*x as f32 * stacks))
+            }
+            BuffModifier::PickupRadius(x) => {
+                (Stat::Magnet, ModifierKind::Multiplicative(x * stacks))
+            }
+            BuffModifier::Invulnerability => return None,
+        };
+        Some(StatModifier {
+            stat,
+            source: ModifierSource::Buff,
+            kind,
+        })
+    }
+}
+
+/// The stats of the player, every gameplay system reads the player's numbers from here
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum Stat {
+    MoveSpeed,  // The velocity added each frame the player moves
+    Might,      // Multiplies the damage of the player's spells
+    Cooldown,   // Multiplies the cooldown of the player's spells and dash, lower is faster
+    Area,       // Multiplies the size of the player's aoe spells
+    Duration,   // Multiplies the duration of the player's spells and buffs
+    Amount,     // Extra projectiles for each projectile spell
+    Luck,       // Multiplies the drop chance of the monsters and the weight of the rarer cards
+    Growth,     // Multiplies the experience picked up
+    Greed,      // Multiplies the value of the coins picked up
+    Magnet,     // The radius of the magnet collider
+    MaxHealth,  // Sets MaxHealth
+    MaxStamina, // Sets MaxStamina
+    CritChance, // From 0.0 to 1.0
+    CritDamage, // Multiplies the damage of a critical hit
+}
+
+/// Where a stat modifier comes from,
+/// used to remove all the modifiers of a source at once, i.e., when buffs expire
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModifierSource {
+    Card,
+    Buff,
+    Meta,
+    Item,
+}
+
+/// Additive modifiers are added to the base,
+/// multiplicative ones are summed and then multiply it, i.e., 0.5 is +50%
+#[derive(Clone, Copy, Debug)]
+pub enum ModifierKind {
+    Additive(f32),
+    Multiplicative(f32),
+}
+
+#[derive(Clone, Debug)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub source: ModifierSource,
+    pub kind: ModifierKind,
+}
+
+/// The base values of the player's stats and the modifiers applied to them,
+/// the final value of a stat is (base + additive) * (1 + multiplicative)
+#[derive(Component, Debug, Clone)]
+pub struct PlayerStats {
+    base: HashMap<Stat, f32>,
+    modifiers: Vec<StatModifier>,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        let base = HashMap::from_iter([
+            (Stat::MoveSpeed, 25.0),
+            (Stat::Might, 1.0),
+            (Stat::Cooldown, 1.0),
+            (Stat::Area, 1.0),
+            (Stat::Duration, 1.0),
+            (Stat::Amount, 0.0),
+            (Stat::Luck, 1.0),
+            (Stat::Growth, 1.0),
+            (Stat::Greed, 1.0),
+            (Stat::Magnet, 1.0),
+            (Stat::MaxHealth, 100.0),
+            (Stat::MaxStamina, 10.0),
+            (Stat::CritChance, 0.05),
+            (Stat::CritDamage, 1.5),
+        ]);
+        Self {
+            base,
+            modifiers: Vec::new(),
+        }
+    }
+}
+
+impl PlayerStats {
+    /// The final value of the stat, never negative
+    pub fn get(&self, stat: Stat) -> f32 {
+        let base = self.base.get(&stat).copied().unwrap_or_default();
+        let (additive, multiplicative) =
+            self.modifiers
+                .iter()
+                .filter(|m| m.stat == stat)
+                .fold((0.0, 0.0), |(add, mult), m| match m.kind {
+                    ModifierKind::Additive(x) => (add + x, mult),
+                    ModifierKind::Multiplicative(x) => (add, mult + x),
+                });
+        ((base + additive) * (1.0 + multiplicative)).max(0.0)
+    }
+
+    /// The multiplier for the cooldowns, capped so they can't reach 0
+    pub fn cooldown(&self) -> f32 {
+        self.get(Stat::Cooldown).max(0.1)
+    }
+
+    pub fn set_base(&mut self, stat: Stat, value: f32) {
+        self.base.insert(stat, value);
+    }
+
+    pub fn add_modifier(&mut self, modifier: StatModifier) {
+        self.modifiers.push(modifier);
+    }
+
+    /// Removes all the modifiers that came from the source
+    pub fn clear_source(&mut self, source: ModifierSource) {
+        self.modifiers.retain(|m| m.source != source);
+    }
+}
+
+/// Defines what happens when a buff is cast while it is still active
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq)]
+pub enum BuffStacking {
+    #[default]
+    Refresh, // Resets the duration
+    Stack(u8), // Adds a stack, up to the limit, and resets the duration
+    Ignore,    // The new cast is discarded
+}
+
+/// A buff currently applied to the entity
+#[derive(Clone, Debug)]
+pub struct ActiveBuff {
+    pub name: String,
+    pub sprite: String,
+    pub modifiers: Vec<BuffModifier>,
+    pub timer: Timer,
+    pub stacks: u8,
+}
+
+/// The buffs currently applied to the entity,
+/// each modifier is multiplied by the number of stacks of its buff
+#[derive(Component, Debug, Default)]
+pub struct ActiveBuffs(pub Vec<ActiveBuff>);
+
+impl ActiveBuffs {
+    /// Applies the buff, following its stacking rule if it is already active
+    pub fn apply(
+        &mut self,
+        name: &str,
+        modifiers: &[BuffModifier],
+        timer: Timer,
+        stacking: BuffStacking,
+        sprite: &str,
+    ) {
+        if let Some(active) = self.0.iter_mut().find(|b| b.name == name) {
+            match stacking {
+                BuffStacking::Refresh => active.timer.reset(),
+                BuffStacking::Stack(max_stacks) => {
+                    active.stacks = (active.stacks + 1).min(max_stacks.max(1));
+                    active.timer.reset();
+                }
+                BuffStacking::Ignore => {}
+            }
+        } else {
+            self.0.push(ActiveBuff {
+                name: name.to_string(),
+                sprite: sprite.to_string(),
+                modifiers: modifiers.to_vec(),
+                timer,
+                stacks: 1,
+            });
+        }
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        self.0.iter().any(|buff| {
+            buff.modifiers
+                .iter()
+                .any(|m| matches!(m, BuffModifier::Invulnerability))
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -560,7 +1223,8 @@ pub enum CardType {
     Spell,
 }
 
-#[derive(Deserialize, Debug, Component, Clone, PartialEq, Hash)]
+/// Ordered from the most common to the rarest
+#[derive(Deserialize, Debug, Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum CardRarity {
     Common,
     Uncommon,
@@ -572,29 +1236,87 @@ pub enum CardRarity {
 
 #[derive(Deserialize, Debug, Component, Clone)]
 pub enum PowerUp {
-    HealthUp(u8),
-    AttackUp(u8),
-    ShieldUp(u8),
-    SpeedUp(u8),
-    LootUp(u8),
-    DamageUp(u8),
-    ExpUp(u8),
-    StaminaUp(u8),
+    HealthUp(u8),  // Max health, the current health is raised by the same amount
+    AttackUp(u8),  // Flat damage added to each hit of the player's spells
+    ShieldUp(u8),  // Max shield
+    SpeedUp(u8),   // Percentage of move speed
+    LootUp(u8),    // Percentage of luck
+    DamageUp(u8),  // Percentage of might
+    ExpUp(u8),     // Percentage of growth
+    StaminaUp(u8), // Max stamina
+    ArmorUp(u8),   // Flat armor
 }
 
+impl PowerUp {
+    /// The modifier to the player's stats granted by the power up,
+    /// the flat damage, the shield and the armor aren't stats
+    pub fn stat_modifier(&self) -> Option<StatModifier> {
+        let (stat, kind) = match self {
+            PowerUp::HealthUp(x) => (Stat::MaxHealth, ModifierKind::Additive(*x as f32)),
+            PowerUp::StaminaUp(x) => (Stat::MaxStamina, ModifierKind::Additive(*x as f32)),
+            PowerUp::SpeedUp(x) => (
+                Stat::MoveSpeed,
+                ModifierKind::Multiplicative(*x as f32 / 100.0),
+            ),
+            PowerUp::LootUp(x) => (Stat::Luck, ModifierKind::Multiplicative(*x as f32 / 100.0)),
+            PowerUp::DamageUp(x) => (Stat::Might, ModifierKind::Multiplicative(*x as f32 / 100.0)),
+            PowerUp::ExpUp(x) => (
+                Stat::Growth,
+                ModifierKind::Multiplicative(*x as f32 / 100.0),
+            ),
+            PowerUp::AttackUp(_) | PowerUp::ShieldUp(_) | PowerUp::ArmorUp(_) => return None,
+        };
+        Some(StatModifier {
+            stat,
+            source: ModifierSource::Card,
+            kind,
+        })
+    }
+}
+
+/// The drawback of a cursed card, it lasts for the rest of the run
 #[derive(Deserialize, Debug, Component, Clone)]
 pub enum PermanentDebuff {
-    HealthDown(u8),
-    AttackDown(u8),
-    StaminaDown(u8),
-    ExpDown(u8),
-    SpeedDown(u8),
+    HealthDown(u8),  // Max health, the current health is lowered by the same amount
+    AttackDown(u8),  // Flat damage removed from each hit of the player's spells
+    StaminaDown(u8), // Max stamina
+    ExpDown(u8),     // Percentage of growth
+    SpeedDown(u8),   // Percentage of move speed
+}
+
+impl PermanentDebuff {
+    /// The negative modifier to the player's stats of the debuff,
+    /// the flat damage isn't a stat
+    pub fn stat_modifier(&self) -> Option<StatModifier> {
+        let (stat, kind) = match self {
+            PermanentDebuff::HealthDown(x) => {
+                (Stat::MaxHealth, ModifierKind::Additive(-(*x as f32)))
+            }
+            PermanentDebuff::StaminaDown(x) => {
+                (Stat::MaxStamina, ModifierKind::Additive(-(*x as f32)))
+            }
+            PermanentDebuff::ExpDown(x) => (
+                Stat::Growth,
+                ModifierKind::Multiplicative(-(*x as f32) / 100.0),
+            ),
+            PermanentDebuff::SpeedDown(x) => (
+                Stat::MoveSpeed,
+                ModifierKind::Multiplicative(-(*x as f32) / 100.0),
+            ),
+            PermanentDebuff::AttackDown(_) => return None,
+        };
+        Some(StatModifier {
+            stat,
+            source: ModifierSource::Card,
+            kind,
+        })
+    }
 }
 
 /// Every time the player levels up, spawn 3 new cards,
 /// every level is an index of the array, the player chooses always from the index 0 and then the vec is shifted,
 /// If there are no valid remaining cards it will be a None in the array,
-/// TODO! if the vec[0] is [None, None, None] grants the player resource and consume the array.
+/// an offer of [None, None, None] is consumed and the player gets the empty offer reward instead.
 /// If the vec is empty it means the player can't currently choose a new card.
 #[derive(Resource, Debug, Default)]
 pub struct ChooseACard {
@@ -608,7 +1330,6 @@ pub struct RemainingCardsByType {
     pub spell_cards: Vec<String>,
 }
 
-
 // Note: Spell cards need to verify if the spell exists
 #[derive(Clone, Deserialize, Debug)]
 pub struct CardsTemplate {
@@ -622,11 +1343,200 @@ pub struct CardsTemplate {
     pub spell: Option<String>,
     pub debuff: Option<PermanentDebuff>,
     pub max_level_bonus: Option<PowerUp>,
+    pub triggers: Option<Vec<CardTriggerTemplate>>,
+}
+
+/// What fires the effect of a card trigger
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq)]
+pub enum CardTrigger {
+    OnHit,              // A hit of the player's spells on a monster
+    OnCrit,             // A critical hit of the player's spells on a monster
+    OnKill,             // A monster dies
+    OnDamaged,          // The player loses health
+    OnPickup(ItemType), // The player picks up an item of the type
+}
+
+/// The effect of a card trigger, it happens where the trigger happened when it has a position,
+/// otherwise around the player
+#[derive(Clone, Deserialize, Debug)]
+pub enum TriggerEffect {
+    Heal(usize),
+    // Drops an item of the loot table
+    Drop(u8),
+    Nova {
+        damage: usize,
+        radius: f32,
+        damage_type: DamageType,
+    },
+    Chain {
+        damage: usize,
+        jumps: u8,
+        radius: f32,
+        damage_type: DamageType,
+    },
+    Buff {
+        name: String,
+        modifiers: Vec<BuffModifier>,
+        duration: f32,
+        stacking: BuffStacking,
+    },
+}
+
+/// An effect granted by a card, fired by a trigger
+#[derive(Clone, Deserialize, Debug)]
+pub struct CardTriggerTemplate {
+    pub trigger: CardTrigger,
+    pub effect: TriggerEffect,
+    pub chance: Option<f32>,   // 0.0 - 1.0, the effect always happens if None
+    pub cooldown: Option<f32>, // The time before the trigger can fire again
+}
+
+/// A card trigger of the player with its internal cooldown
+#[derive(Debug)]
+pub struct ActiveTrigger {
+    pub sprite: String, // The sprite of the card, shown for the buffs granted by the trigger
+    pub template: CardTriggerTemplate,
+    pub cooldown: Timer,
+}
+
+impl ActiveTrigger {
+    /// The trigger can fire right away
+    pub fn new(sprite: String, template: CardTriggerTemplate) -> Self {
+        let mut cooldown = Timer::from_seconds(template.cooldown.unwrap_or(0.0), TimerMode::Once);
+        let duration = cooldown.duration();
+        cooldown.tick(duration);
+        Self {
+            sprite,
+            template,
+            cooldown,
+        }
+    }
 }
 
+/// The triggers granted by the cards the player picked
+#[derive(Component, Debug, Default)]
+pub struct CardTriggers(pub Vec<ActiveTrigger>);
+
 #[derive(Clone, Deserialize, Debug, Resource)]
 pub struct CardsTemplates {
     pub cards: HashMap<String, CardsTemplate>,
+    #[serde(default)]
+    pub draw_rules: CardDrawRules,
+}
+
+/// How the cards are drawn when the player levels up
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct CardDrawRules {
+    /// The weight of each rarity, a rarity that isn't listed is never drawn
+    pub rarity_weights: HashMap<CardRarity, f32>,
+    /// How much each point of luck above 1 raises the weight of the rarities above common,
+    /// multiplied by how many steps above common the rarity is
+    pub luck_scaling: f32,
+    /// Multiplies the weight of the spell cards
+    pub spell_weight: f32,
+    pub pity: Vec<PityRule>,
+    /// The uses of each card action for a run
+    pub rerolls: u8,
+    pub skips: u8,
+    pub banishes: u8,
+    /// The experience given when the cards are skipped
+    pub skip_experience: usize,
+    /// Given instead of the cards when there are no cards left to offer
+    pub empty_offer_reward: Option<PowerUp>,
+}
+
+impl Default for CardDrawRules {
+    fn default() -> Self {
+        Self {
+            rarity_weights: [
+                (CardRarity::Common, 60.0),
+                (CardRarity::Uncommon, 25.0),
+                (CardRarity::Rare, 10.0),
+                (CardRarity::Mythical, 4.0),
+                (CardRarity::Legendary, 1.0),
+                (CardRarity::Ultimate, 0.25),
+            ]
+            .into_iter()
+            .collect(),
+            luck_scaling: 0.5,
+            spell_weight: 1.0,
+            pity: Vec::new(),
+            rerolls: 2,
+            skips: 2,
+            banishes: 1,
+            skip_experience: 50,
+            empty_offer_reward: Some(PowerUp::HealthUp(10)),
+        }
+    }
+}
+
+impl CardDrawRules {
+    /// The weight of the rarity with the player's luck
+    pub fn rarity_weight(&self, rarity: CardRarity, luck: f32) -> f32 {
+        let weight = self
+            .rarity_weights
+            .get(&rarity)
+            .copied()
+            .unwrap_or_default();
+        let steps = rarity as u8 as f32;
+        (weight * (1.0 + self.luck_scaling * (luck - 1.0) * steps)).max(0.0)
+    }
+}
+
+/// After `draws` level ups in a row without a card of `rarity` or rarer,
+/// the next level up has one, if there is any left
+#[derive(Clone, Deserialize, Debug)]
+pub struct PityRule {
+    pub rarity: CardRarity,
+    pub draws: u8,
+}
+
+/// The card actions left and the pity counters of the run,
+/// it is initialized in the template loading stage, after the cards are loaded
+#[derive(Resource, Debug)]
+pub struct CardDraws {
+    pub rerolls: u8,
+    pub skips: u8,
+    pub banishes: u8,
+    /// The level ups in a row without the rarity of each pity rule, in the same order
+    pub pity_counters: Vec<u8>,
+    /// The cards banished during the run, they are never drawn nor offered as evolutions
+    pub banished: HashSet<String>,
+}
+
+impl CardDraws {
+    pub fn new(rules: &CardDrawRules) -> Self {
+        Self {
+            rerolls: rules.rerolls,
+            skips: rules.skips,
+            banishes: rules.banishes,
+            pity_counters: vec![0; rules.pity.len()],
+            banished: HashSet::new(),
+        }
+    }
+
+    /// The rarest rarity that the next cards must have
+    pub fn pity_due(&self, rules: &CardDrawRules) -> Option<CardRarity> {
+        rules
+            .pity
+            .iter()
+            .zip(self.pity_counters.iter())
+            .filter(|(rule, counter)| **counter + 1 >= rule.draws)
+            .map(|(rule, _)| rule.rarity)
+            .max()
+    }
+
+    /// Resets the counters of the pity rules met by the drawn rarities, increments the others
+    pub fn update_pity(&mut self, rules: &CardDrawRules, drawn: &[CardRarity]) {
+        for (rule, counter) in rules.pity.iter().zip(self.pity_counters.iter_mut()) {
+            if drawn.iter().any(|rarity| *rarity >= rule.rarity) {
+                *counter = 0;
+            } else {
+                *counter = counter.saturating_add(1);
+            }
+        }
+    }
 }
 
 impl FromReader<File> for CardsTemplates {
@@ -637,7 +1547,7 @@ impl FromReader<File> for CardsTemplates {
 
 /// A structure that keeps track of the current cards
 /// the player has chosen, and their level,
-/// needs to be initialized in the template loading stage, after the cards are loaded
+/// it is initialized in the template loading stage, after the cards are loaded
 #[derive(Resource, Debug)]
 pub struct CurrentCards {
     pub cards: HashMap<String, u8>,
@@ -41,7 +41,82 @@ pub enum QualityMonster {
 
 #[derive(Clone, Deserialize, Debug)]
 pub enum MonsterAI {
-    StateMachine,
-    BehaviorTree,
+    StateMachine(StateMachineTemplate),
+    // The key of a tree of behaviortrees.ron, evaluated every tick_rate seconds
+    // or every tick if none, the current action keeps going between evaluations
+    BehaviorTree {
+        tree: String,
+        tick_rate: Option<f32>,
+    },
     BigBrain,
 }
+
+/// All possible actions a monster can take,
+/// actions are issued by a transition system or a behavior tree system
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Reflect)]
+pub enum Action {
+    Chase,
+    Retreat,
+    Charge,     // Dashes in the direction locked when entering the state or while channeling
+    Channeling, // Stands still, telegraphs the next action
+    Idle,
+    RandomWalk,
+    CircleAround,
+}
+
+/// The states of a monster, it starts in the initial state
+/// and follows the first transition whose conditions all hold
+#[derive(Clone, Deserialize, Debug)]
+pub struct StateMachineTemplate {
+    pub initial: String,
+    pub states: HashMap<String, MonsterStateTemplate>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct MonsterStateTemplate {
+    pub action: Action,
+    // Multiplier of the monster's movespeed while in the state, 1.0 if none
+    pub speed: Option<f32>,
+    #[serde(default)]
+    pub transitions: Vec<StateTransition>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct StateTransition {
+    pub to: String,
+    pub when: Vec<StateCondition>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub enum StateCondition {
+    TargetCloserThan(f32),
+    TargetFartherThan(f32),
+    HealthBelow(f32), // Fraction of the max health
+    HealthAbove(f32),
+    TimeInState(f32), // Seconds, since the current action started in a behavior tree
+    TargetVisible,    // No wall between the monster and its target
+    TargetHidden,
+}
+
+/// A node of a behavior tree, a node succeeds, fails or keeps running,
+/// the composite nodes resume from their running child on the next evaluation
+#[derive(Clone, Deserialize, Debug)]
+pub enum BehaviorNode {
+    // Runs its children in order until one of them fails
+    Sequence(Vec<BehaviorNode>),
+    // Runs its children in order until one of them succeeds
+    Selector(Vec<BehaviorNode>),
+    // Turns a success into a failure and the other way around
+    Invert(Box<BehaviorNode>),
+    // Succeeds even when its child fails
+    AlwaysSucceed(Box<BehaviorNode>),
+    // Fails without running its child until the seconds have passed since the child last ran
+    Cooldown(f32, Box<BehaviorNode>),
+    Condition(StateCondition),
+    // Starts the action, keeps running for the duration if there is one then succeeds
+    Act {
+        action: Action,
+        speed: Option<f32>, // Multiplier of the monster's movespeed, 1.0 if none
+        duration: Option<f32>,
+    },
+}
@@ -7,7 +7,7 @@ use ar_cards::CardPlugin;
 use ar_conf::{BG_COLOR, PFPS};
 use ar_core::{
     AISet, AppState, AudioSet, BattleSet, CameraSet, CardSet, InputSet, ItemsSet, LevelSet, MapSet,
-    MonsterSet, ParticleSet, PlayerSet, SpellSet, UiSet, UtilSet,
+    MonsterSet, ParticleSet, PauseState, PlayerSet, SpellSet, UiSet, UtilSet,
 };
 use ar_enemies::MonsterSprites;
 use ar_input::InputPlugin;
@@ -155,6 +155,9 @@ impl Plugin for GamePlugin {
         );
 
         app.init_state::<AppState>()
+            .init_state::<PauseState>()
+            .add_systems(OnExit(PauseState::NotPaused), pause_physics)
+            .add_systems(OnEnter(PauseState::NotPaused), unpause_physics)
             .add_plugins(
                 ProgressPlugin::<AppState>::new()
                     .with_state_transition(AppState::LoadingAssets, AppState::LoadingTemplates),
@@ -197,26 +200,44 @@ impl Plugin for GamePlugin {
                 Update,
                 (
                     AudioSet.run_if(in_state(AppState::InBattle)),
-                    BattleSet.run_if(in_state(AppState::InBattle)),
+                    BattleSet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
                     CameraSet.run_if(in_state(AppState::InBattle)),
                     InputSet.run_if(in_state(AppState::InBattle)),
-                    PlayerSet.run_if(in_state(AppState::InBattle)),
+                    PlayerSet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
                     MapSet.run_if(in_state(AppState::InBattle)),
-                    MonsterSet.run_if(in_state(AppState::InBattle)),
+                    MonsterSet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
                 ),
             )
             .configure_sets(
                 FixedUpdate,
                 (
-                    AISet.run_if(in_state(AppState::InBattle)),
-                    MonsterSet.run_if(in_state(AppState::InBattle)),
+                    // the gameplay stops while the game is paused, the cards and the ui keep going
+                    AISet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
+                    MonsterSet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
                     UiSet.run_if(in_state(AppState::InBattle)),
-                    UtilSet.run_if(in_state(AppState::InBattle)),
-                    BattleSet.run_if(in_state(AppState::InBattle)),
-                    ParticleSet.run_if(in_state(AppState::InBattle)),
+                    UtilSet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
+                    BattleSet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
+                    ParticleSet
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
                     ItemsSet
                         .after(BattleSet)
-                        .run_if(in_state(AppState::InBattle)),
+                        .run_if(in_state(AppState::InBattle))
+                        .run_if(in_state(PauseState::NotPaused)),
                     LevelSet.run_if(in_state(AppState::InBattle)),
                     CardSet.run_if(in_state(AppState::InBattle)),
                     InputSet.run_if(in_state(AppState::InBattle)),
@@ -228,3 +249,12 @@ impl Plugin for GamePlugin {
             .configure_sets(OnEnter(AppState::InBattle), LevelSet.before(SpellSet));
     }
 }
+
+/// The physics have their own clock, it is paused with the rest of the game
+fn pause_physics(mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.pause();
+}
+
+fn unpause_physics(mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.unpause();
+}
@@ -1,8 +1,8 @@
 #[cfg(debug_assertions)]
 use ar_core::OneShotSystems;
 use ar_core::{
-    BoostUsage, CameraFollowState, ChangeBackgroundEvent, ChosenCard, DashUsage, InputSet,
-    PlayerDirection, PlayerMarker, ZoomIn, ZoomOut,
+    BoostUsage, CameraFollowState, CardAction, ChangeBackgroundEvent, ChosenCard, DashUsage,
+    InputSet, PlayerDirection, PlayerMarker, ZoomIn, ZoomOut,
 };
 
 use bevy::prelude::*;
@@ -62,6 +62,9 @@ pub enum Action {
     ChooseCard1,
     ChooseCard2,
     ChooseCard3,
+    RerollCards,
+    SkipCards,
+    BanishCard, // Held while choosing a card to banish it instead
     #[cfg(debug_assertions)]
     GiveExpDebug,
     #[cfg(debug_assertions)]
@@ -96,6 +99,9 @@ impl Action {
         input_map.insert(Self::ChooseCard1, KeyCode::Digit1);
         input_map.insert(Self::ChooseCard2, KeyCode::Digit2);
         input_map.insert(Self::ChooseCard3, KeyCode::Digit3);
+        input_map.insert(Self::RerollCards, KeyCode::KeyR);
+        input_map.insert(Self::SkipCards, KeyCode::KeyX);
+        input_map.insert(Self::BanishCard, KeyCode::KeyB);
         #[cfg(debug_assertions)]
         input_map.insert(Self::GiveExpDebug, KeyCode::KeyM);
         #[cfg(debug_assertions)]
@@ -175,13 +181,27 @@ pub fn zoom_in_out(
 pub fn choose_card(
     action_state: Res<ActionState<Action>>,
     mut ev_choose_card: EventWriter<ChosenCard>,
+    mut ev_card_action: EventWriter<CardAction>,
 ) {
-    if action_state.just_pressed(&Action::ChooseCard1) {
-        ev_choose_card.send(ChosenCard(0));
+    let card = if action_state.just_pressed(&Action::ChooseCard1) {
+        Some(0)
     } else if action_state.just_pressed(&Action::ChooseCard2) {
-        ev_choose_card.send(ChosenCard(1));
+        Some(1)
     } else if action_state.just_pressed(&Action::ChooseCard3) {
-        ev_choose_card.send(ChosenCard(2));
+        Some(2)
+    } else {
+        None
+    };
+    if let Some(card) = card {
+        if action_state.pressed(&Action::BanishCard) {
+            ev_card_action.send(CardAction::Banish(card));
+        } else {
+            ev_choose_card.send(ChosenCard(card));
+        }
+    } else if action_state.just_pressed(&Action::RerollCards) {
+        ev_card_action.send(CardAction::Reroll);
+    } else if action_state.just_pressed(&Action::SkipCards) {
+        ev_card_action.send(CardAction::Skip);
     }
 }
 
@@ -2,8 +2,8 @@
 // and can be picked up
 
 use ar_core::{
-    DropItemEvent, ItemComponent, ItemMarker, ItemType, ItemsSet, Layer, PickupEvent,
-    PlayerExperience, PlayerMarker,
+    DropItemEvent, Health, ItemComponent, ItemMarker, ItemType, ItemsSet, Layer, MaxHealth,
+    PickupEvent, PlayerExperience, PlayerMarker, PlayerStats, Stat,
 };
 use ar_template::items::{ItemTemplates, ItemsUtil};
 use avian2d::prelude::*;
@@ -98,14 +98,24 @@ pub fn item_spawner(
 pub fn pickup_item(
     mut commands: Commands,
     mut ev_pickup: EventReader<PickupEvent>,
-    mut player_experience: Query<&mut PlayerExperience, With<PlayerMarker>>,
+    mut player: Query<
+        (&mut PlayerExperience, &PlayerStats, &mut Health, &MaxHealth),
+        With<PlayerMarker>,
+    >,
     query: Query<&ItemComponent, With<ItemMarker>>,
 ) {
-    let mut player_experience = player_experience.single_mut();
+    let (mut player_experience, stats, mut health, max_health) = player.single_mut();
+    let growth = stats.get(Stat::Growth);
     for ev in ev_pickup.read() {
         if let Ok(item) = query.get(ev.entity) {
-            if item.item_type == ItemType::ExperienceOrb {
-                player_experience.0 += item.value;
+            match item.item_type {
+                ItemType::ExperienceOrb => {
+                    player_experience.0 += (item.value as f32 * growth).round() as usize;
+                }
+                ItemType::Heal => {
+                    health.0 = (health.0 + item.value).min(max_health.0);
+                }
+                _ => {}
             }
         }
         commands.entity(ev.entity).despawn_recursive();
@@ -21,7 +21,6 @@ impl Plugin for LevelPlugin {
                 FixedUpdate,
                 (
                     check_for_level_up.in_set(LevelSet),
-                    level_up.in_set(LevelSet),
                     choosing_card.in_set(LevelSet),
                 )
                     .chain(),
@@ -47,30 +46,24 @@ fn setup_generate_level_table(mut commands: Commands) {
     commands.insert_resource(LevelTable { table });
 }
 
+/// Sends an event for every level gained, a big amount of experience can give several levels
+/// at once, each of them is offered its own cards
 fn check_for_level_up(
     mut query: Query<(&mut PlayerExperience, &mut PlayerLevel), With<PlayerMarker>>,
     level_table: Res<LevelTable>,
     mut ev_levelup: EventWriter<LevelUpEvent>,
 ) {
     let (mut exp, mut level) = query.single_mut();
-    if exp.0 >= level_table.table[level.0 as usize] && level.0 < MAX_LEVEL {
+    while level.0 < MAX_LEVEL && exp.0 >= level_table.table[level.0 as usize] {
         exp.0 -= level_table.table[level.0 as usize];
         level.0 += 1;
         ev_levelup.send(LevelUpEvent { level: level.0 });
     }
 }
 
-pub fn level_up(
-    mut ev_levelup: EventReader<LevelUpEvent>,
-    mut available_cards: ResMut<AvailableCards>,
-) {
-    for _ in ev_levelup.read() {
-        available_cards.0 += 1;
-    }
-}
-
 // Checks if the chosen card is valid and sends an event
-// with its name to apply the effects of the card
+// with its name to apply the effects of the card,
+// only the first offer of the queue can be chosen
 pub fn choosing_card(
     mut ev_choosing_card: EventReader<ChosenCard>,
     mut ev_card_name: EventWriter<ApplyCard>,
@@ -78,18 +71,21 @@ pub fn choosing_card(
     choose_card: Res<ChooseACard>,
 ) {
     if available_cards.0 == 0 {
+        ev_choosing_card.clear();
         return;
     }
-    for card_number in ev_choosing_card.read() {
-        if card_number.0 > 2 {
+    let Some(offer) = choose_card.cards.first() else {
+        ev_choosing_card.clear();
+        return;
+    };
+    // one card per offer, the next offer is shown once this one is consumed
+    if let Some(card_number) = ev_choosing_card.read().last() {
+        let Some(Some(card)) = offer.get(card_number.0 as usize) else {
             info!("Invalid card number");
             return;
-        }
-        let index = card_number.0 as usize;
-        if let Some(card) = &choose_card.cards[0][index] {
-            ev_card_name.send(ApplyCard {
-                card: card.to_string(),
-            });
-        }
+        };
+        ev_card_name.send(ApplyCard {
+            card: card.to_string(),
+        });
     }
 }
@@ -1,4 +1,4 @@
-use ar_core::{AppState, Layer, MapSet};
+use ar_core::{AppState, Layer, MapSet, WallMarker};
 use avian2d::prelude::*;
 use bevy::prelude::*;
 use bevy_ecs_tiled::prelude::*;
@@ -30,7 +30,11 @@ fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>) {
         .insert(Collider::rectangle(1960.0, 16.0))
         .insert(Mass(500.0))
         .insert(Transform::from_xyz(0.0, 640.0, 100.0))
-        .insert(CollisionLayers::new([Layer::Wall], [Layer::Player]));
+        .insert(CollisionLayers::new(
+            [Layer::Wall],
+            [Layer::Player, Layer::PlayerProjectile],
+        ))
+        .insert(WallMarker);
 
     // bottom wall
     commands
@@ -38,7 +42,11 @@ fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>) {
         .insert(Collider::rectangle(1960.0, 16.0))
         .insert(Mass(500.0))
         .insert(Transform::from_xyz(0.0, -624.0, 100.0))
-        .insert(CollisionLayers::new([Layer::Wall], [Layer::Player]));
+        .insert(CollisionLayers::new(
+            [Layer::Wall],
+            [Layer::Player, Layer::PlayerProjectile],
+        ))
+        .insert(WallMarker);
 
     // left wall
     commands
@@ -46,7 +54,11 @@ fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>) {
         .insert(Collider::rectangle(16.0, 1320.0))
         .insert(Mass(500.0))
         .insert(Transform::from_xyz(-944.0, 0.0, 100.0))
-        .insert(CollisionLayers::new([Layer::Wall], [Layer::Player]));
+        .insert(CollisionLayers::new(
+            [Layer::Wall],
+            [Layer::Player, Layer::PlayerProjectile],
+        ))
+        .insert(WallMarker);
 
     // right wall
     commands
@@ -54,5 +66,9 @@ fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>) {
         .insert(Collider::rectangle(16.0, 1320.0))
         .insert(Mass(500.0))
         .insert(Transform::from_xyz(960.0, 0.0, 100.0))
-        .insert(CollisionLayers::new([Layer::Wall], [Layer::Player]));
+        .insert(CollisionLayers::new(
+            [Layer::Wall],
+            [Layer::Player, Layer::PlayerProjectile],
+        ))
+        .insert(WallMarker);
 }
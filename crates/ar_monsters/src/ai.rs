@@ -1,28 +1,33 @@
-use crate::{BaseSpeed, LinearVelocity, PlayerMarker};
+use crate::{
+    BaseSpeed, GlobalEntropy, Health, Layer, LinearVelocity, MaxHealth, MonsterTemplates,
+    PlayerMarker, RngCore, Staggered, StatusEffects, Stunned, WyRand,
+};
 use ar_core::AISet;
+pub use ar_enemies::Action;
+use ar_enemies::{MonsterAI, StateCondition, StateMachineTemplate};
+use avian2d::prelude::{SpatialQuery, SpatialQueryFilter};
 use bevy::prelude::*;
+use bevy::time::Stopwatch;
+use std::f32::consts::TAU;
+
+/// The tint of a monster telegraphing its next action
+const CHANNELING_COLOR: Color = Color::srgb(1.0, 0.45, 0.45);
 
 pub struct AIPlugin;
 
 impl Plugin for AIPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, chase.in_set(AISet));
+        app.add_systems(
+            FixedUpdate,
+            (
+                chase,
+                (tick_monster_actions, update_monster_states, move_monsters).chain(),
+            )
+                .in_set(AISet),
+        );
     }
 }
 
-/// All possible actions a monster can take,
-/// actions are issued by a transition system or a behavior tree system
-#[derive(Clone, Debug)]
-pub enum Action {
-    Chase,
-    Retreat,
-    Charge,
-    Channeling,
-    Idle,
-    RandomWalk,
-    CircleAround,
-}
-
 /// The target of the chaser
 #[derive(Clone, Component, Reflect)]
 #[component(storage = "SparseSet")]
@@ -39,16 +44,280 @@ pub enum ChasingBehavior {
     Charging,
 }
 
-/// Adjusts the speed and direction the monster should be moving towards its target
+/// Adjusts the speed and direction the monster should be moving towards its target,
+/// scaled by its slows, stuns, freezes and roots,
+/// staggered monsters are skipped so the knockback is visible
+#[allow(clippy::type_complexity)]
 fn chase(
-    mut query: Query<(&GlobalTransform, &BaseSpeed, &mut LinearVelocity), With<Chase>>,
+    mut query: Query<
+        (
+            &GlobalTransform,
+            &BaseSpeed,
+            &mut LinearVelocity,
+            Option<&StatusEffects>,
+        ),
+        (With<Chase>, Without<Staggered>),
+    >,
     player: Query<&GlobalTransform, With<PlayerMarker>>,
 ) {
     let player_position = player.single();
-    for (transform, base_speed, mut velocity) in query.iter_mut() {
+    for (transform, base_speed, mut velocity, effects) in query.iter_mut() {
         let speed = (player_position.translation() - transform.translation()).normalize_or_zero()
-            * base_speed.0;
+            * base_speed.0
+            * effects.map_or(1.0, StatusEffects::speed_multiplier);
         velocity.x = speed.x;
         velocity.y = speed.y;
     }
 }
+
+/// The action a monster driven by a state machine or a behavior tree is doing
+#[derive(Clone, Component, Reflect, Debug)]
+pub struct MonsterAction {
+    pub target: Entity,
+    pub action: Action,
+    pub speed: f32, // Multiplier of the monster's BaseSpeed
+    // The direction of the dash or the walk, locked when the action starts
+    pub direction: Vec2,
+    pub elapsed: Stopwatch,
+}
+
+impl MonsterAction {
+    pub fn new(target: Entity, action: Action, speed: f32) -> Self {
+        Self {
+            target,
+            action,
+            speed,
+            direction: Vec2::ZERO,
+            elapsed: Stopwatch::new(),
+        }
+    }
+
+    /// Starts another action and locks its direction, returns the previous action
+    pub(crate) fn switch(
+        &mut self,
+        action: Action,
+        speed: f32,
+        to_target: Vec2,
+        rng: &mut GlobalEntropy<WyRand>,
+    ) -> Action {
+        let previous = self.action;
+        self.direction = match action {
+            Action::Channeling => to_target.normalize_or_zero(),
+            // a dash keeps the direction that was telegraphed
+            Action::Charge if previous == Action::Channeling => self.direction,
+            Action::Charge => to_target.normalize_or_zero(),
+            Action::RandomWalk => {
+                let angle = rng.next_u32() as f32 / u32::MAX as f32 * TAU;
+                Vec2::from_angle(angle)
+            }
+            _ => Vec2::ZERO,
+        };
+        self.action = action;
+        self.speed = speed;
+        self.elapsed.reset();
+        previous
+    }
+}
+
+/// Tints the monster while it telegraphs, then gives it back the tint of its status effects
+pub(crate) fn tint_channeling(
+    previous: Action,
+    next: Action,
+    sprite: &mut Sprite,
+    status_effects: Option<&StatusEffects>,
+) {
+    if next == Action::Channeling {
+        sprite.color = CHANNELING_COLOR;
+    } else if previous == Action::Channeling {
+        sprite.color = status_effects
+            .and_then(StatusEffects::tint_kind)
+            .map_or(Color::WHITE, |kind| kind.tint());
+    }
+}
+
+/// What a monster knows when its AI checks a condition
+pub(crate) struct Senses<'a, 'w, 's> {
+    pub spatial_query: &'a SpatialQuery<'w, 's>,
+    pub position: Vec2,
+    pub to_target: Vec2,
+    pub health_fraction: f32,
+    pub elapsed: f32, // Seconds since the current action started
+}
+
+impl<'a, 'w, 's> Senses<'a, 'w, 's> {
+    pub fn new(
+        spatial_query: &'a SpatialQuery<'w, 's>,
+        transform: &GlobalTransform,
+        target: &GlobalTransform,
+        health: &Health,
+        max_health: &MaxHealth,
+        action: &MonsterAction,
+    ) -> Self {
+        let position = transform.translation().truncate();
+        Self {
+            spatial_query,
+            position,
+            to_target: target.translation().truncate() - position,
+            health_fraction: health.0 as f32 / max_health.0.max(1) as f32,
+            elapsed: action.elapsed.elapsed_secs(),
+        }
+    }
+
+    pub fn holds(&self, condition: &StateCondition) -> bool {
+        let distance = self.to_target.length();
+        match condition {
+            StateCondition::TargetCloserThan(range) => distance < *range,
+            StateCondition::TargetFartherThan(range) => distance > *range,
+            StateCondition::HealthBelow(fraction) => self.health_fraction < *fraction,
+            StateCondition::HealthAbove(fraction) => self.health_fraction > *fraction,
+            StateCondition::TimeInState(secs) => self.elapsed >= *secs,
+            StateCondition::TargetVisible => self.target_visible(),
+            StateCondition::TargetHidden => !self.target_visible(),
+        }
+    }
+
+    fn target_visible(&self) -> bool {
+        let wall_filter = SpatialQueryFilter::from_mask(Layer::Wall);
+        match Dir2::new(self.to_target) {
+            Ok(direction) => self
+                .spatial_query
+                .cast_ray(
+                    self.position,
+                    direction,
+                    self.to_target.length(),
+                    true,
+                    &wall_filter,
+                )
+                .is_none(),
+            // the monster is on its target
+            Err(_) => true,
+        }
+    }
+}
+
+/// The current state of a monster driven by the state machine of its template
+#[derive(Clone, Component, Reflect, Debug)]
+pub struct MonsterStateMachine {
+    pub monster: String, // The key of the monster template
+    pub state: String,
+}
+
+fn state_machine<'a>(
+    templates: &'a MonsterTemplates,
+    monster: &str,
+) -> Option<&'a StateMachineTemplate> {
+    match templates.templates.get(monster)?.ai.as_ref()? {
+        MonsterAI::StateMachine(machine) => Some(machine),
+        _ => None,
+    }
+}
+
+pub(crate) fn tick_monster_actions(time: Res<Time>, mut query: Query<&mut MonsterAction>) {
+    for mut action in query.iter_mut() {
+        action.elapsed.tick(time.delta());
+    }
+}
+
+/// Follows the first transition of the current state whose conditions all hold,
+/// stunned monsters stay in their state
+#[allow(clippy::type_complexity)]
+fn update_monster_states(
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    spatial_query: SpatialQuery,
+    monster_templates: Res<MonsterTemplates>,
+    mut query: Query<
+        (
+            &mut MonsterStateMachine,
+            &mut MonsterAction,
+            &GlobalTransform,
+            &Health,
+            &MaxHealth,
+            &mut Sprite,
+            Option<&StatusEffects>,
+        ),
+        Without<Stunned>,
+    >,
+    targets: Query<&GlobalTransform>,
+) {
+    for (mut machine, mut action, transform, health, max_health, mut sprite, status_effects) in
+        query.iter_mut()
+    {
+        let Some(template) = state_machine(&monster_templates, &machine.monster) else {
+            continue;
+        };
+        let Some(state) = template.states.get(&machine.state) else {
+            continue;
+        };
+        let Ok(target) = targets.get(action.target) else {
+            continue;
+        };
+        let senses = Senses::new(
+            &spatial_query,
+            transform,
+            target,
+            health,
+            max_health,
+            &action,
+        );
+        let Some(transition) = state
+            .transitions
+            .iter()
+            .find(|transition| transition.when.iter().all(|c| senses.holds(c)))
+        else {
+            continue;
+        };
+        let Some(next) = template.states.get(&transition.to) else {
+            continue;
+        };
+
+        machine.state = transition.to.clone();
+        let previous = action.switch(
+            next.action,
+            next.speed.unwrap_or(1.0),
+            senses.to_target,
+            &mut rng,
+        );
+        tint_channeling(previous, next.action, &mut sprite, status_effects);
+    }
+}
+
+/// Moves the monsters according to their current action,
+/// their status effects and staggered monsters are handled like in chase
+#[allow(clippy::type_complexity)]
+pub(crate) fn move_monsters(
+    mut query: Query<
+        (
+            Entity,
+            &MonsterAction,
+            &GlobalTransform,
+            &BaseSpeed,
+            &mut LinearVelocity,
+            Option<&StatusEffects>,
+        ),
+        Without<Staggered>,
+    >,
+    targets: Query<&GlobalTransform>,
+) {
+    for (entity, action, transform, base_speed, mut velocity, effects) in query.iter_mut() {
+        let Ok(target) = targets.get(action.target) else {
+            velocity.0 = Vec2::ZERO;
+            continue;
+        };
+        let to_target = (target.translation() - transform.translation())
+            .truncate()
+            .normalize_or_zero();
+        let direction = match action.action {
+            Action::Chase => to_target,
+            Action::Retreat => -to_target,
+            Action::Charge | Action::RandomWalk => action.direction,
+            Action::Channeling | Action::Idle => Vec2::ZERO,
+            // half of the monsters circle clockwise
+            Action::CircleAround if entity.index() % 2 == 0 => to_target.perp(),
+            Action::CircleAround => -to_target.perp(),
+        };
+        velocity.0 = direction
+            * base_speed.0
+            * action.speed
+            * effects.map_or(1.0, StatusEffects::speed_multiplier);
+    }
+}
@@ -0,0 +1,317 @@
+use crate::ai::{move_monsters, tick_monster_actions, tint_channeling, MonsterAction, Senses};
+use crate::{GlobalEntropy, Health, MaxHealth, StatusEffects, Stunned, WyRand};
+use ar_core::AISet;
+use ar_enemies::{Action, BehaviorNode};
+use ar_template::behaviortrees::BehaviorTreeTemplates;
+use avian2d::prelude::SpatialQuery;
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+use bevy::utils::HashMap;
+
+pub struct BehaviorTreePlugin;
+
+impl Plugin for BehaviorTreePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            tick_behavior_trees
+                .after(tick_monster_actions)
+                .before(move_monsters)
+                .in_set(AISet),
+        );
+
+        #[cfg(debug_assertions)]
+        app.register_type::<ActiveBehaviorNode>().add_systems(
+            FixedUpdate,
+            show_active_behavior_nodes
+                .after(tick_behavior_trees)
+                .in_set(AISet),
+        );
+    }
+}
+
+/// The behavior tree of a monster and what it remembers between evaluations
+#[derive(Clone, Component, Debug)]
+pub struct MonsterBehaviorTree {
+    pub tree: String, // The key of the tree in BehaviorTreeTemplates
+    pub tick_rate: f32,
+    clock: Stopwatch,
+    next_evaluation: f32,
+    // The child indices from the root to the action that is running, empty if none is
+    running: Vec<usize>,
+    // The child indices from the root to the last action that was started
+    active: Vec<usize>,
+    // When each cooldown node can run its child again, on the clock of the tree
+    cooldowns: HashMap<Vec<usize>, f32>,
+}
+
+impl MonsterBehaviorTree {
+    pub fn new(tree: String, tick_rate: Option<f32>) -> Self {
+        Self {
+            tree,
+            tick_rate: tick_rate.unwrap_or(0.0),
+            clock: Stopwatch::new(),
+            next_evaluation: 0.0,
+            running: Vec::new(),
+            active: Vec::new(),
+            cooldowns: HashMap::default(),
+        }
+    }
+}
+
+/// The node of the behavior tree the monster is in, shown in the inspector
+#[cfg(debug_assertions)]
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct ActiveBehaviorNode(pub String);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+/// One evaluation of a behavior tree from its root
+struct Evaluation<'a, 'w, 's> {
+    senses: Senses<'a, 'w, 's>,
+    action: &'a mut MonsterAction,
+    rng: &'a mut GlobalEntropy<WyRand>,
+    tree: &'a mut MonsterBehaviorTree,
+    // The running path of the previous evaluation
+    resuming: Vec<usize>,
+    path: Vec<usize>,
+    // The action before the first action started by the evaluation
+    switched_from: Option<Action>,
+}
+
+impl Evaluation<'_, '_, '_> {
+    /// The child a composite node resumes from, the one leading to the running action
+    fn resumed_child(&self) -> usize {
+        let depth = self.path.len();
+        if self.resuming.len() > depth && self.resuming.starts_with(&self.path) {
+            self.resuming[depth]
+        } else {
+            0
+        }
+    }
+
+    fn run_child(&mut self, index: usize, child: &BehaviorNode) -> Status {
+        self.path.push(index);
+        let status = self.run(child);
+        self.path.pop();
+        status
+    }
+
+    fn run(&mut self, node: &BehaviorNode) -> Status {
+        match node {
+            BehaviorNode::Sequence(children) => {
+                for (index, child) in children.iter().enumerate().skip(self.resumed_child()) {
+                    match self.run_child(index, child) {
+                        Status::Success => continue,
+                        status => return status,
+                    }
+                }
+                Status::Success
+            }
+            BehaviorNode::Selector(children) => {
+                for (index, child) in children.iter().enumerate().skip(self.resumed_child()) {
+                    match self.run_child(index, child) {
+                        Status::Failure => continue,
+                        status => return status,
+                    }
+                }
+                Status::Failure
+            }
+            BehaviorNode::Invert(child) => match self.run_child(0, child) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            BehaviorNode::AlwaysSucceed(child) => match self.run_child(0, child) {
+                Status::Running => Status::Running,
+                _ => Status::Success,
+            },
+            BehaviorNode::Cooldown(secs, child) => {
+                let now = self.tree.clock.elapsed_secs();
+                let resuming =
+                    self.resuming.len() > self.path.len() && self.resuming.starts_with(&self.path);
+                let cooling_down = self
+                    .tree
+                    .cooldowns
+                    .get(&self.path)
+                    .is_some_and(|t| now < *t);
+                if !resuming && cooling_down {
+                    return Status::Failure;
+                }
+                let status = self.run_child(0, child);
+                if status != Status::Running {
+                    self.tree.cooldowns.insert(self.path.clone(), now + secs);
+                }
+                status
+            }
+            BehaviorNode::Condition(condition) => {
+                if self.senses.holds(condition) {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            }
+            BehaviorNode::Act {
+                action,
+                speed,
+                duration,
+            } => {
+                let resuming = self.resuming == self.path;
+                // an action without duration keeps going while the tree keeps choosing it
+                let ongoing = duration.is_none()
+                    && self.tree.active == self.path
+                    && self.action.action == *action;
+                if !resuming && !ongoing {
+                    let previous = self.action.switch(
+                        *action,
+                        speed.unwrap_or(1.0),
+                        self.senses.to_target,
+                        self.rng,
+                    );
+                    self.switched_from.get_or_insert(previous);
+                    self.senses.elapsed = 0.0;
+                    self.tree.active = self.path.clone();
+                }
+                match duration {
+                    Some(duration) if self.action.elapsed.elapsed_secs() < *duration => {
+                        self.tree.running = self.path.clone();
+                        Status::Running
+                    }
+                    _ => Status::Success,
+                }
+            }
+        }
+    }
+}
+
+/// Evaluates the behavior tree of each monster when its tick rate allows it,
+/// the action started by the tree is then carried out by move_monsters,
+/// stunned monsters aren't evaluated
+#[allow(clippy::type_complexity)]
+fn tick_behavior_trees(
+    time: Res<Time>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    spatial_query: SpatialQuery,
+    behavior_trees: Res<BehaviorTreeTemplates>,
+    mut query: Query<
+        (
+            &mut MonsterBehaviorTree,
+            &mut MonsterAction,
+            &GlobalTransform,
+            &Health,
+            &MaxHealth,
+            &mut Sprite,
+            Option<&StatusEffects>,
+        ),
+        Without<Stunned>,
+    >,
+    targets: Query<&GlobalTransform>,
+) {
+    for (mut tree, mut action, transform, health, max_health, mut sprite, status_effects) in
+        query.iter_mut()
+    {
+        tree.clock.tick(time.delta());
+        let now = tree.clock.elapsed_secs();
+        if now < tree.next_evaluation {
+            continue;
+        }
+        tree.next_evaluation = now + tree.tick_rate;
+        let Some(root) = behavior_trees.trees.get(&tree.tree) else {
+            continue;
+        };
+        let Ok(target) = targets.get(action.target) else {
+            continue;
+        };
+
+        let senses = Senses::new(
+            &spatial_query,
+            transform,
+            target,
+            health,
+            max_health,
+            &action,
+        );
+        let resuming = std::mem::take(&mut tree.running);
+        let mut evaluation = Evaluation {
+            senses,
+            action: &mut *action,
+            rng: &mut *rng,
+            tree: &mut *tree,
+            resuming,
+            path: Vec::new(),
+            switched_from: None,
+        };
+        evaluation.run(root);
+        let switched_from = evaluation.switched_from;
+
+        if let Some(previous) = switched_from {
+            tint_channeling(previous, action.action, &mut sprite, status_effects);
+        }
+    }
+}
+
+/// Names the nodes from the root to the last action started by the tree
+#[cfg(debug_assertions)]
+fn show_active_behavior_nodes(
+    mut commands: Commands,
+    behavior_trees: Res<BehaviorTreeTemplates>,
+    mut query: Query<(
+        Entity,
+        &MonsterBehaviorTree,
+        Option<&mut ActiveBehaviorNode>,
+    )>,
+) {
+    for (entity, tree, active_node) in query.iter_mut() {
+        let Some(mut node) = behavior_trees.trees.get(&tree.tree) else {
+            continue;
+        };
+        let mut names = vec![node_name(node)];
+        for index in tree.active.iter() {
+            let child = match node {
+                BehaviorNode::Sequence(children) | BehaviorNode::Selector(children) => {
+                    children.get(*index)
+                }
+                BehaviorNode::Invert(child)
+                | BehaviorNode::AlwaysSucceed(child)
+                | BehaviorNode::Cooldown(_, child) => Some(child.as_ref()),
+                BehaviorNode::Condition(_) | BehaviorNode::Act { .. } => None,
+            };
+            let Some(child) = child else {
+                break;
+            };
+            node = child;
+            names.push(node_name(node));
+        }
+        let path = names.join(" > ");
+
+        match active_node {
+            Some(mut active_node) => {
+                if active_node.0 != path {
+                    active_node.0 = path;
+                }
+            }
+            None => {
+                commands.entity(entity).insert(ActiveBehaviorNode(path));
+            }
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+fn node_name(node: &BehaviorNode) -> String {
+    match node {
+        BehaviorNode::Sequence(_) => "Sequence".to_string(),
+        BehaviorNode::Selector(_) => "Selector".to_string(),
+        BehaviorNode::Invert(_) => "Invert".to_string(),
+        BehaviorNode::AlwaysSucceed(_) => "AlwaysSucceed".to_string(),
+        BehaviorNode::Cooldown(secs, _) => format!("Cooldown({})", secs),
+        BehaviorNode::Condition(condition) => format!("{:?}", condition),
+        BehaviorNode::Act { action, .. } => format!("{:?}", action),
+    }
+}
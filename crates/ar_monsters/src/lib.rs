@@ -2,10 +2,12 @@
 
 pub mod ai;
 pub mod animation;
+pub mod behaviortree;
 pub mod spawn;
 
-use crate::ai::{AIPlugin, Chase};
+use crate::ai::{AIPlugin, Action, Chase, MonsterAction, MonsterStateMachine};
 use crate::animation::MonsterAnimationPlugin;
+use crate::behaviortree::{BehaviorTreePlugin, MonsterBehaviorTree};
 use avian2d::prelude::*;
 use bevy::math::vec2;
 use bevy::prelude::*;
@@ -18,11 +20,12 @@ use spawn::SpawnPlugin;
 
 use ar_camera::{ARENA_HEIGHT_ZOOMOUT, ARENA_WIDTH_ZOOMOUT};
 use ar_core::{
-    AppState, BaseSpeed, Cooldown, Damage, DropsChance, GameScore, Health, Layer, LootTables,
-    MinutesSurvived, MonsterLayoutType, MonsterMarker, MonsterMarkerSmall, MonsterSet,
-    MonstersAlive, PlayerHandler, PlayerMarker,
+    AppState, BaseSpeed, Cooldown, Damage, DropsChance, GameScore, Health, KnockbackResistance,
+    Layer, LootTables, MaxHealth, MinutesSurvived, MonsterLayoutType, MonsterMarker,
+    MonsterMarkerSmall, MonsterSet, MonstersAlive, OnHitEffect, PlayerHandler, PlayerMarker,
+    Resistances, Shield, Staggered, StatusEffects, Stunned,
 };
-use ar_enemies::MonsterSprites;
+use ar_enemies::{MonsterAI, MonsterSprites};
 use ar_template::monsters::{MonsterDifficultyLists, MonsterTemplates};
 
 pub struct MonsterPlugin;
@@ -35,6 +38,7 @@ impl Plugin for MonsterPlugin {
         )
         .add_plugins(SpawnPlugin)
         .add_plugins(AIPlugin)
+        .add_plugins(BehaviorTreePlugin)
         .add_plugins(MonsterAnimationPlugin);
     }
 }
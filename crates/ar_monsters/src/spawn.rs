@@ -83,22 +83,27 @@ fn spawn_monsters(
 
         let drop_chance: DropsChance = monster.drops_chance.unwrap_or(1.0).into();
 
-        let (layout, collider_size, mass) = match monster.layout {
-            MonsterLayoutType::Small => (monster_sprites.monster_layout_small.clone(), 8.0, 20.0),
+        let (layout, collider_size, mass, knockback_resistance) = match monster.layout {
+            MonsterLayoutType::Small => {
+                (monster_sprites.monster_layout_small.clone(), 8.0, 20.0, 0.0)
+            }
             MonsterLayoutType::Medium => (
                 monster_sprites.monster_layout_large_four.clone(),
                 12.0,
                 35.0,
+                0.2,
             ),
             MonsterLayoutType::Large => (
                 monster_sprites.monster_layout_large_nine.clone(),
                 16.0,
                 50.0,
+                0.5,
             ),
             MonsterLayoutType::Boss => (
                 monster_sprites.monster_layout_large_nine.clone(),
                 32.0,
                 100.0,
+                0.85,
             ),
         };
 
@@ -122,24 +127,72 @@ fn spawn_monsters(
             .insert(Mass(mass))
             .insert(LinearVelocity(speed))
             .insert(AngularVelocity(0.0))
+            .insert(ExternalImpulse::default())
+            .insert(KnockbackResistance(knockback_resistance))
             .insert(Collider::circle(collider_size))
             .insert(LockedAxes::ROTATION_LOCKED)
             .insert(CollisionLayers::new(
                 [Layer::Monster],
-                [Layer::Player, Layer::PlayerProjectile],
+                [Layer::Player, Layer::PlayerProjectile, Layer::PlayerAOE],
             ))
             .insert(Health(monster.hp))
+            .insert(MaxHealth(monster.hp))
             .insert(Damage(monster.damage))
             .insert(loot_tables)
             .insert(drop_chance)
+            .insert(StatusEffects::default())
             .insert(Cooldown(Timer::from_seconds(0.55, TimerMode::Repeating))) // Animation timer
-            .insert(Chase {
-                target: target.player_id,
-            })
             .id();
+        // monsters without a state machine or a behavior tree keep chasing their target
+        match &monster.ai {
+            Some(MonsterAI::StateMachine(machine)) => {
+                let (action, speed) = machine
+                    .states
+                    .get(&machine.initial)
+                    .map_or((Action::Idle, 1.0), |state| {
+                        (state.action, state.speed.unwrap_or(1.0))
+                    });
+                commands
+                    .entity(monster_id)
+                    .insert(MonsterAction::new(target.player_id, action, speed))
+                    .insert(MonsterStateMachine {
+                        monster: name.clone(),
+                        state: machine.initial.clone(),
+                    });
+            }
+            Some(MonsterAI::BehaviorTree { tree, tick_rate }) => {
+                commands
+                    .entity(monster_id)
+                    .insert(MonsterAction::new(target.player_id, Action::Idle, 1.0))
+                    .insert(MonsterBehaviorTree::new(tree.clone(), *tick_rate));
+            }
+            _ => {
+                commands.entity(monster_id).insert(Chase {
+                    target: target.player_id,
+                });
+            }
+        }
         if let MonsterLayoutType::Small = monster.layout {
             commands.entity(monster_id).insert(MonsterMarkerSmall);
         }
+        if let Some(effect) = &monster.attack_effect {
+            commands
+                .entity(monster_id)
+                .insert(OnHitEffect(effect.clone()));
+        }
+        if let Some(armor) = &monster.armor {
+            commands.entity(monster_id).insert(armor.clone());
+        }
+        if let Some(resistances) = &monster.resistances {
+            commands.entity(monster_id).insert(resistances.clone());
+        }
+        if let Some(shield) = &monster.shield {
+            commands.entity(monster_id).insert(Shield::new(
+                shield.max,
+                shield.regen,
+                shield.regen_delay,
+            ));
+        }
         spawn_count -= 1;
     }
 }
@@ -1,9 +1,14 @@
 use ar_core::{
-    AppState, Cooldown, CurrentStamina, Damage, Health, Layer, MagnetHandler, MagnetMarker,
-    MaxHealth, MaxStamina, PlayerExperience, PlayerHandler, PlayerInvulnerableFrames,
-    PlayerLastDirection, PlayerLevel, PlayerMarker, PlayerSet, StaminaRegen, RemainingCardsByType,
+    ActiveBuffs, AppState, Armor, CardTriggers, CardsTemplates, Cooldown, CurrentCards,
+    CurrentStamina, Damage, Health, Layer, MagnetHandler, MagnetMarker, MaxHealth, MaxStamina,
+    PlayerDash, PlayerExperience, PlayerHandler, PlayerInvulnerableFrames, PlayerLastDirection,
+    PlayerLevel, PlayerMarker, PlayerSet, PlayerStats, RemainingCardsByType, Shield, StaminaRegen,
+    StatusEffects,
+};
+use ar_spells::generator::{
+    OwnedAOESpells, OwnedAuraSpells, OwnedBeamSpells, OwnedBuffSpells, OwnedChainSpells,
+    OwnedOrbitSpells, OwnedProjectileSpells, OwnedTrapSpells, ProjectileSpells,
 };
-use ar_spells::generator::{OwnedAOESpells, OwnedProjectileSpells, ProjectileSpells};
 use avian2d::prelude::*;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
@@ -47,8 +52,15 @@ fn spawn_player(mut commands: Commands, sheet_handle: Res<SheetHandle>) {
         .insert(Cooldown(Timer::from_seconds(0.24, TimerMode::Repeating))) // Animation cooldown, for attacks it should be a Cooldown in its own child
         .insert(CollisionLayers::new(
             [Layer::Player],
-            [Layer::Monster, Layer::MonsterProjectile, Layer::Wall],
+            [
+                Layer::Monster,
+                Layer::MonsterProjectile,
+                Layer::Wall,
+                Layer::Hazard,
+            ],
         ))
+        // Used for the contact damage of everything overlapping the player
+        .insert(CollidingEntities::default())
         .insert(PlayerInvulnerableFrames {
             // Player invulnerability after getting hit
             timer: Timer::from_seconds(0.5, TimerMode::Once),
@@ -61,12 +73,25 @@ fn spawn_player(mut commands: Commands, sheet_handle: Res<SheetHandle>) {
         .insert(PlayerLevel(1))
         .insert(Health(100))
         .insert(MaxHealth(100))
-        .insert(CurrentStamina(1.))
+        .insert(CurrentStamina(10.))
         .insert(MaxStamina(10.))
-        .insert(StaminaRegen(0.1))
-        .insert(Damage(1))
+        .insert(StaminaRegen(1.0))
+        // Flat damage added to each hit of the player's spells
+        .insert(Damage(0))
+        .insert(PlayerStats::default())
         .insert(OwnedProjectileSpells { spells: vec![] })
         .insert(OwnedAOESpells { spells: vec![] })
+        .insert(OwnedBuffSpells { spells: vec![] })
+        .insert(OwnedChainSpells { spells: vec![] })
+        .insert(OwnedBeamSpells { spells: vec![] })
+        .insert(OwnedOrbitSpells { spells: vec![] })
+        .insert(OwnedTrapSpells { spells: vec![] })
+        .insert(OwnedAuraSpells { spells: vec![] })
+        .insert(ActiveBuffs::default())
+        .insert(CardTriggers::default())
+        .insert(StatusEffects::default())
+        .insert(Armor::default())
+        .insert(Shield::new(0.0, 1.0, 3.0))
         // This child is the magnet collider, used for item pickup
         .with_children(|children| {
             magnet_id = children
@@ -83,6 +108,18 @@ fn spawn_player(mut commands: Commands, sheet_handle: Res<SheetHandle>) {
     commands.insert_resource(PlayerHandler { player_id });
     commands.insert_resource(PlayerLastDirection { direction: Vec2::Y });
     commands.insert_resource(MagnetHandler { magnet_id });
+
+    let mut dash_cooldown = Timer::from_seconds(1.0, TimerMode::Once);
+    // the player can dash as soon as the battle starts
+    dash_cooldown.tick(dash_cooldown.duration());
+    commands.insert_resource(PlayerDash {
+        timer: dash_cooldown,
+        dashing: false,
+        distance: 64.0,
+        duration: 0.15,
+        stamina_cost: 2.0,
+        pass_through: true,
+    });
 }
 
 // TODO! This should be chosen by the player at the menu before the game starts
@@ -90,6 +127,8 @@ fn spawn_player(mut commands: Commands, sheet_handle: Res<SheetHandle>) {
 fn setup_basic_spell(
     mut player_spells: Query<&mut OwnedProjectileSpells, With<PlayerMarker>>,
     loaded_projectile_spells: Res<ProjectileSpells>,
+    cards_templates: Res<CardsTemplates>,
+    mut current_cards: ResMut<CurrentCards>,
     mut remaining_cards: ResMut<RemainingCardsByType>,
 ) {
     let mut player_spells = player_spells.single_mut();
@@ -99,7 +138,16 @@ fn setup_basic_spell(
         .expect("no throwdagger in loaded spells");
     player_spells.spells.push(spell.clone());
 
-    if let Some(idx) = remaining_cards.spell_cards.iter().position(|v| *v == "throwdagger") {
-        remaining_cards.spell_cards.swap_remove(idx);
+    // the card of the spell counts as picked once
+    for (key, card) in cards_templates.cards.iter() {
+        if card.spell.as_deref() != Some("throwdagger") {
+            continue;
+        }
+        current_cards.cards.insert(key.clone(), 1);
+        if card.max_level <= 1 {
+            if let Some(idx) = remaining_cards.spell_cards.iter().position(|v| v == key) {
+                remaining_cards.spell_cards.swap_remove(idx);
+            }
+        }
     }
 }
@@ -28,6 +28,42 @@ pub struct AOESpells {
     pub aoe_spells: HashMap<String, SpellAOE>,
 }
 
+/// A hashmap of buffspells that exist
+#[derive(Resource, Debug)]
+pub struct BuffSpells {
+    pub buff_spells: HashMap<String, SpellBuff>,
+}
+
+/// A hashmap of chainspells that exist
+#[derive(Resource, Debug)]
+pub struct ChainSpells {
+    pub chain_spells: HashMap<String, SpellChain>,
+}
+
+/// A hashmap of beamspells that exist
+#[derive(Resource, Debug)]
+pub struct BeamSpells {
+    pub beam_spells: HashMap<String, SpellBeam>,
+}
+
+/// A hashmap of orbitspells that exist
+#[derive(Resource, Debug)]
+pub struct OrbitSpells {
+    pub orbit_spells: HashMap<String, SpellOrbit>,
+}
+
+/// A hashmap of trapspells that exist
+#[derive(Resource, Debug)]
+pub struct TrapSpells {
+    pub trap_spells: HashMap<String, SpellTrap>,
+}
+
+/// A hashmap of auraspells that exist
+#[derive(Resource, Debug)]
+pub struct AuraSpells {
+    pub aura_spells: HashMap<String, SpellAura>,
+}
+
 #[derive(Component)]
 pub struct OwnedAOESpells {
     pub spells: Vec<SpellAOE>,
@@ -39,6 +75,42 @@ pub struct OwnedProjectileSpells {
     pub spells: Vec<SpellProjectile>,
 }
 
+/// The spells of the type Buff that the Entity has
+#[derive(Component)]
+pub struct OwnedBuffSpells {
+    pub spells: Vec<SpellBuff>,
+}
+
+/// The spells of the type Chain that the Entity has
+#[derive(Component)]
+pub struct OwnedChainSpells {
+    pub spells: Vec<SpellChain>,
+}
+
+/// The spells of the type Beam that the Entity has
+#[derive(Component)]
+pub struct OwnedBeamSpells {
+    pub spells: Vec<SpellBeam>,
+}
+
+/// The spells of the type Orbit that the Entity has
+#[derive(Component)]
+pub struct OwnedOrbitSpells {
+    pub spells: Vec<SpellOrbit>,
+}
+
+/// The spells of the type Trap that the Entity has
+#[derive(Component)]
+pub struct OwnedTrapSpells {
+    pub spells: Vec<SpellTrap>,
+}
+
+/// The spells of the type Aura that the Entity has
+#[derive(Component)]
+pub struct OwnedAuraSpells {
+    pub spells: Vec<SpellAura>,
+}
+
 /// The spells of the type Swing that the Entity has
 #[derive(Component)]
 pub struct OwnedSwingSpells {
@@ -58,6 +130,12 @@ pub struct SpellProjectile {
     pub mass: f32,
     pub lifetime: f32,
     pub penetration: u8,
+    pub bounce: u8,
+    pub ricochet: u8,
+    pub homing: Option<Homing>,
+    pub knockback: Option<f32>,
+    pub effect: Option<StatusEffectTemplate>,
+    pub damage_type: DamageType,
 }
 
 #[derive(Clone, Debug)]
@@ -70,6 +148,89 @@ pub struct SpellAOE {
     pub radius: f32,
     pub knockback: Option<f32>,
     pub distributed: bool,
+    pub anchor: AoEAnchor,
+    pub duration: Option<f32>,
+    pub tick_rate: f32,
+    pub effect: Option<StatusEffectTemplate>,
+    pub damage_type: DamageType,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpellBuff {
+    pub name: String,
+    pub sprite: String,
+    pub cooldown: Timer,
+    pub duration: f32,
+    pub modifiers: Vec<BuffModifier>,
+    pub stacking: BuffStacking,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpellChain {
+    pub name: String,
+    pub sprite: String,
+    pub cooldown: Timer,
+    pub damage: usize,
+    pub jumps: u8,
+    pub radius: f32,
+    pub falloff: f32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpellBeam {
+    pub name: String,
+    pub sprite: String,
+    pub cooldown: Timer,
+    pub damage: usize,
+    pub length: f32,
+    pub width: f32,
+    pub duration: f32,
+    pub tick_rate: f32,
+    pub pierce: bool,
+    pub damage_type: DamageType,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpellOrbit {
+    pub name: String,
+    pub sprite: String,
+    pub cooldown: Timer,
+    pub count: u8,
+    pub damage: usize,
+    pub distance: f32,
+    pub speed: f32,
+    pub radius: f32,
+    pub duration: f32,
+    pub hit_cooldown: f32,
+    pub knockback: Option<f32>,
+    pub damage_type: DamageType,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpellTrap {
+    pub name: String,
+    pub sprite: String,
+    pub cooldown: Timer,
+    pub damage: usize,
+    pub radius: f32,
+    pub arm_time: f32,
+    pub lifetime: f32,
+    pub max_active: u8,
+    pub trigger: TrapTrigger,
+    pub placement: TrapPlacement,
+    pub damage_type: DamageType,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpellAura {
+    pub name: String,
+    pub sprite: String,
+    pub tick_rate: f32,
+    pub damage: usize,
+    pub radius: f32,
+    pub effect: Option<StatusEffectTemplate>,
+    pub damage_type: DamageType,
 }
 
 impl PartialEq for SpellProjectile {
@@ -84,6 +245,42 @@ impl PartialEq for SpellAOE {
     }
 }
 
+impl PartialEq for SpellBuff {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for SpellChain {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for SpellBeam {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for SpellOrbit {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for SpellTrap {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for SpellAura {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SpellProjectileExplosive {
     pub name: String,
@@ -115,11 +312,18 @@ pub struct SpellSwing {
     pub aoe: SpellAOEType,
 }
 
-/// Creates the SpellSwing, SpellProjectile, SpellAOE structs from the SpellTemplates
+/// Creates the SpellSwing, SpellProjectile, SpellAOE, SpellBuff, SpellChain, SpellBeam, SpellOrbit, SpellTrap, SpellAura structs
+/// from the SpellTemplates
 /// Must be run before setup_player as the player is spawned with a spell
 pub fn setup_generate_spells(loaded_spells: Res<SpellTemplates>, mut commands: Commands) {
     let mut projectile_spells = HashMap::new();
     let mut aoe_spells = HashMap::new();
+    let mut buff_spells = HashMap::new();
+    let mut chain_spells = HashMap::new();
+    let mut beam_spells = HashMap::new();
+    let mut orbit_spells = HashMap::new();
+    let mut trap_spells = HashMap::new();
+    let mut aura_spells = HashMap::new();
     for (name, spell) in &loaded_spells.spells {
         match spell.spell_main_type {
             SpellType::Projectile => {
@@ -139,6 +343,12 @@ pub fn setup_generate_spells(loaded_spells: Res<SpellTemplates>, mut commands: C
                     mass: projectile.projectile_mass,
                     lifetime: projectile.projectile_lifetime,
                     penetration: projectile.projectile_penetration.unwrap_or(0),
+                    bounce: projectile.projectile_bounce.unwrap_or(0),
+                    ricochet: projectile.projectile_ricochet.unwrap_or(0),
+                    homing: projectile.projectile_homing,
+                    knockback: projectile.projectile_knockback,
+                    effect: projectile.projectile_effect,
+                    damage_type: projectile.projectile_damage_type.unwrap_or_default(),
                 };
                 projectile_spells.insert(name.clone(), proj);
             }
@@ -157,12 +367,137 @@ pub fn setup_generate_spells(loaded_spells: Res<SpellTemplates>, mut commands: C
                     radius: aoe.aoe_radius,
                     knockback: aoe.aoe_knockback,
                     distributed: aoe.aoe_distributed,
+                    anchor: aoe.aoe_anchor.unwrap_or_default(),
+                    duration: aoe.aoe_duration,
+                    tick_rate: aoe.aoe_tick_rate.unwrap_or(0.5),
+                    effect: aoe.aoe_effect,
+                    damage_type: aoe.aoe_damage_type.unwrap_or_default(),
                 };
                 aoe_spells.insert(name.clone(), aoe);
             }
+            SpellType::Buff => {
+                let buff = spell
+                    .buff_type_struct
+                    .clone()
+                    .expect("Buff Type with no Buff Struct");
+
+                let buff = SpellBuff {
+                    name: spell.name.clone(),
+                    sprite: buff.buff_sprite,
+                    cooldown: Timer::from_seconds(spell.cooldown, TimerMode::Repeating),
+                    duration: buff.buff_duration,
+                    modifiers: buff.buff_modifiers,
+                    stacking: buff.buff_stacking.unwrap_or_default(),
+                };
+                buff_spells.insert(name.clone(), buff);
+            }
+            SpellType::Chain => {
+                let chain = spell
+                    .chain_type_struct
+                    .clone()
+                    .expect("Chain Type with no Chain Struct");
+
+                let chain = SpellChain {
+                    name: spell.name.clone(),
+                    sprite: chain.chain_sprite,
+                    cooldown: Timer::from_seconds(spell.cooldown, TimerMode::Repeating),
+                    damage: chain.chain_damage,
+                    jumps: chain.chain_jumps,
+                    radius: chain.chain_radius,
+                    falloff: chain.chain_falloff.clamp(0.0, 1.0),
+                    damage_type: chain.chain_damage_type.unwrap_or_default(),
+                };
+                chain_spells.insert(name.clone(), chain);
+            }
+            SpellType::Beam => {
+                let beam = spell
+                    .beam_type_struct
+                    .clone()
+                    .expect("Beam Type with no Beam Struct");
+
+                let beam = SpellBeam {
+                    name: spell.name.clone(),
+                    sprite: beam.beam_sprite,
+                    cooldown: Timer::from_seconds(spell.cooldown, TimerMode::Repeating),
+                    damage: beam.beam_damage,
+                    length: beam.beam_length,
+                    width: beam.beam_width,
+                    duration: beam.beam_duration,
+                    tick_rate: beam.beam_tick_rate,
+                    pierce: beam.beam_pierce,
+                    damage_type: beam.beam_damage_type.unwrap_or_default(),
+                };
+                beam_spells.insert(name.clone(), beam);
+            }
+            SpellType::Orbit => {
+                let orbit = spell
+                    .orbit_type_struct
+                    .clone()
+                    .expect("Orbit Type with no Orbit Struct");
+
+                let orbit = SpellOrbit {
+                    name: spell.name.clone(),
+                    sprite: orbit.orbit_sprite,
+                    cooldown: Timer::from_seconds(spell.cooldown, TimerMode::Repeating),
+                    count: orbit.orbit_count,
+                    damage: orbit.orbit_damage,
+                    distance: orbit.orbit_distance,
+                    speed: orbit.orbit_speed,
+                    radius: orbit.orbit_radius,
+                    duration: orbit.orbit_duration,
+                    hit_cooldown: orbit.orbit_hit_cooldown,
+                    knockback: orbit.orbit_knockback,
+                    damage_type: orbit.orbit_damage_type.unwrap_or_default(),
+                };
+                orbit_spells.insert(name.clone(), orbit);
+            }
+            SpellType::Trap => {
+                let trap = spell
+                    .trap_type_struct
+                    .clone()
+                    .expect("Trap Type with no Trap Struct");
+
+                let trap = SpellTrap {
+                    name: spell.name.clone(),
+                    sprite: trap.trap_sprite,
+                    cooldown: Timer::from_seconds(spell.cooldown, TimerMode::Repeating),
+                    damage: trap.trap_damage,
+                    radius: trap.trap_radius,
+                    arm_time: trap.trap_arm_time,
+                    lifetime: trap.trap_lifetime,
+                    max_active: trap.trap_max_active,
+                    trigger: trap.trap_trigger,
+                    placement: trap.trap_placement.unwrap_or_default(),
+                    damage_type: trap.trap_damage_type.unwrap_or_default(),
+                };
+                trap_spells.insert(name.clone(), trap);
+            }
+            SpellType::Aura => {
+                let aura = spell
+                    .aura_type_struct
+                    .clone()
+                    .expect("Aura Type with no Aura Struct");
+
+                let aura = SpellAura {
+                    name: spell.name.clone(),
+                    sprite: aura.aura_sprite,
+                    tick_rate: spell.cooldown,
+                    damage: aura.aura_damage,
+                    radius: aura.aura_radius,
+                    effect: aura.aura_effect,
+                    damage_type: aura.aura_damage_type.unwrap_or_default(),
+                };
+                aura_spells.insert(name.clone(), aura);
+            }
             _ => (),
         }
     }
     commands.insert_resource(ProjectileSpells { projectile_spells });
     commands.insert_resource(AOESpells { aoe_spells });
+    commands.insert_resource(BuffSpells { buff_spells });
+    commands.insert_resource(ChainSpells { chain_spells });
+    commands.insert_resource(BeamSpells { beam_spells });
+    commands.insert_resource(OrbitSpells { orbit_spells });
+    commands.insert_resource(TrapSpells { trap_spells });
+    commands.insert_resource(AuraSpells { aura_spells });
 }
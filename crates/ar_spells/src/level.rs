@@ -0,0 +1,248 @@
+use crate::generator::*;
+use ar_core::{SpellLevelUp, TrapTrigger};
+use bevy::ecs::query::QueryData;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::{Duration, HashMap};
+
+/// The shortest cooldown a spell can reach by leveling up
+const MIN_SPELL_COOLDOWN: f32 = 0.05;
+
+/// Every spell that exists, by spell type
+#[derive(SystemParam)]
+pub struct SpellRegistries<'w> {
+    pub aoe: Res<'w, AOESpells>,
+    pub projectile: Res<'w, ProjectileSpells>,
+    pub buff: Res<'w, BuffSpells>,
+    pub chain: Res<'w, ChainSpells>,
+    pub beam: Res<'w, BeamSpells>,
+    pub orbit: Res<'w, OrbitSpells>,
+    pub trap: Res<'w, TrapSpells>,
+    pub aura: Res<'w, AuraSpells>,
+}
+
+/// The spells of every spell type that the Entity has
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct OwnedSpells {
+    pub aoe: &'static mut OwnedAOESpells,
+    pub projectile: &'static mut OwnedProjectileSpells,
+    pub buff: &'static mut OwnedBuffSpells,
+    pub chain: &'static mut OwnedChainSpells,
+    pub beam: &'static mut OwnedBeamSpells,
+    pub orbit: &'static mut OwnedOrbitSpells,
+    pub trap: &'static mut OwnedTrapSpells,
+    pub aura: &'static mut OwnedAuraSpells,
+}
+
+impl OwnedSpellsItem<'_> {
+    /// Adds the spell to the owned spells of its type,
+    /// returns false if the spell doesn't exist or is already owned
+    pub fn learn(&mut self, registries: &SpellRegistries, spell: &str) -> bool {
+        learn_from(&mut self.aoe.spells, &registries.aoe.aoe_spells, spell)
+            .or_else(|| {
+                learn_from(
+                    &mut self.projectile.spells,
+                    &registries.projectile.projectile_spells,
+                    spell,
+                )
+            })
+            .or_else(|| learn_from(&mut self.buff.spells, &registries.buff.buff_spells, spell))
+            .or_else(|| {
+                learn_from(
+                    &mut self.chain.spells,
+                    &registries.chain.chain_spells,
+                    spell,
+                )
+            })
+            .or_else(|| learn_from(&mut self.beam.spells, &registries.beam.beam_spells, spell))
+            .or_else(|| {
+                learn_from(
+                    &mut self.orbit.spells,
+                    &registries.orbit.orbit_spells,
+                    spell,
+                )
+            })
+            .or_else(|| learn_from(&mut self.trap.spells, &registries.trap.trap_spells, spell))
+            .or_else(|| learn_from(&mut self.aura.spells, &registries.aura.aura_spells, spell))
+            .unwrap_or(false)
+    }
+
+    /// Applies the deltas of a level to the owned spell,
+    /// returns false if the spell isn't owned
+    pub fn level_up(&mut self, spell: &str, delta: &SpellLevelUp) -> bool {
+        level_up_in(&mut self.aoe.spells, spell, delta)
+            || level_up_in(&mut self.projectile.spells, spell, delta)
+            || level_up_in(&mut self.buff.spells, spell, delta)
+            || level_up_in(&mut self.chain.spells, spell, delta)
+            || level_up_in(&mut self.beam.spells, spell, delta)
+            || level_up_in(&mut self.orbit.spells, spell, delta)
+            || level_up_in(&mut self.trap.spells, spell, delta)
+            || level_up_in(&mut self.aura.spells, spell, delta)
+    }
+
+    /// Removes the spell from the owned spells of its type, when it evolves into another one,
+    /// returns false if the spell isn't owned
+    pub fn forget(&mut self, spell: &str) -> bool {
+        forget_in(&mut self.aoe.spells, spell)
+            || forget_in(&mut self.projectile.spells, spell)
+            || forget_in(&mut self.buff.spells, spell)
+            || forget_in(&mut self.chain.spells, spell)
+            || forget_in(&mut self.beam.spells, spell)
+            || forget_in(&mut self.orbit.spells, spell)
+            || forget_in(&mut self.trap.spells, spell)
+            || forget_in(&mut self.aura.spells, spell)
+    }
+}
+
+/// Returns None if the spell isn't of the registry's type
+fn learn_from<T: Clone + PartialEq>(
+    owned: &mut Vec<T>,
+    registry: &HashMap<String, T>,
+    spell: &str,
+) -> Option<bool> {
+    let spell = registry.get(spell)?;
+    if owned.contains(spell) {
+        return Some(false);
+    }
+    owned.push(spell.clone());
+    Some(true)
+}
+
+fn level_up_in<T: SpellLevel>(owned: &mut [T], spell: &str, delta: &SpellLevelUp) -> bool {
+    match owned.iter_mut().find(|s| s.name() == spell) {
+        Some(owned_spell) => {
+            owned_spell.level_up(delta);
+            true
+        }
+        None => false,
+    }
+}
+
+fn forget_in<T: SpellLevel>(owned: &mut Vec<T>, spell: &str) -> bool {
+    let owned_before = owned.len();
+    owned.retain(|s| s.name() != spell);
+    owned.len() != owned_before
+}
+
+/// A spell that can be leveled up with the deltas of its template
+pub trait SpellLevel {
+    fn name(&self) -> &str;
+    fn level_up(&mut self, delta: &SpellLevelUp);
+}
+
+fn reduce_cooldown(cooldown: &mut Timer, delta: &SpellLevelUp) {
+    if let Some(reduction) = delta.cooldown {
+        let secs = cooldown.duration().as_secs_f32() * (1.0 - reduction);
+        cooldown.set_duration(Duration::from_secs_f32(secs.max(MIN_SPELL_COOLDOWN)));
+    }
+}
+
+fn area_multiplier(delta: &SpellLevelUp) -> f32 {
+    1.0 + delta.area.unwrap_or(0.0)
+}
+
+impl SpellLevel for SpellProjectile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        reduce_cooldown(&mut self.cooldown, delta);
+        self.damage += delta.damage.unwrap_or(0);
+        self.count = self.count.saturating_add(delta.count.unwrap_or(0));
+        self.radius *= area_multiplier(delta);
+    }
+}
+
+impl SpellLevel for SpellAOE {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        reduce_cooldown(&mut self.cooldown, delta);
+        self.damage += delta.damage.unwrap_or(0);
+        self.radius *= area_multiplier(delta);
+    }
+}
+
+impl SpellLevel for SpellBuff {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        reduce_cooldown(&mut self.cooldown, delta);
+    }
+}
+
+impl SpellLevel for SpellChain {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        reduce_cooldown(&mut self.cooldown, delta);
+        self.damage += delta.damage.unwrap_or(0);
+        self.jumps = self.jumps.saturating_add(delta.count.unwrap_or(0));
+        self.radius *= area_multiplier(delta);
+    }
+}
+
+impl SpellLevel for SpellBeam {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        reduce_cooldown(&mut self.cooldown, delta);
+        self.damage += delta.damage.unwrap_or(0);
+        self.length *= area_multiplier(delta);
+        self.width *= area_multiplier(delta);
+    }
+}
+
+impl SpellLevel for SpellOrbit {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        reduce_cooldown(&mut self.cooldown, delta);
+        self.damage += delta.damage.unwrap_or(0);
+        self.count = self.count.saturating_add(delta.count.unwrap_or(0));
+        self.distance *= area_multiplier(delta);
+        self.radius *= area_multiplier(delta);
+    }
+}
+
+impl SpellLevel for SpellTrap {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        reduce_cooldown(&mut self.cooldown, delta);
+        self.damage += delta.damage.unwrap_or(0);
+        self.max_active = self.max_active.saturating_add(delta.count.unwrap_or(0));
+        self.radius *= area_multiplier(delta);
+        if let TrapTrigger::Explode(radius) = &mut self.trigger {
+            *radius *= area_multiplier(delta);
+        }
+    }
+}
+
+impl SpellLevel for SpellAura {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level_up(&mut self, delta: &SpellLevelUp) {
+        if let Some(reduction) = delta.cooldown {
+            self.tick_rate = (self.tick_rate * (1.0 - reduction)).max(MIN_SPELL_COOLDOWN);
+        }
+        self.damage += delta.damage.unwrap_or(0);
+        self.radius *= area_multiplier(delta);
+    }
+}
@@ -1,7 +1,11 @@
 pub mod generator;
+pub mod level;
 
 use crate::generator::GenerateSpellsPlugin;
-use ar_core::{AppState, ProjectilePattern, SpellAOEType, SpellSet, SpellType};
+use ar_core::{
+    AoEAnchor, AppState, BuffModifier, BuffStacking, DamageType, Homing, ProjectilePattern,
+    SpellAOEType, SpellSet, SpellType, StatusEffectTemplate, TrapPlacement, TrapTrigger,
+};
 use ar_template::spells::SpellTemplates;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
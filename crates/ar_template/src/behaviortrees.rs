@@ -0,0 +1,34 @@
+use crate::monsters::MonsterTemplates;
+use crate::{Deserialize, File, FromReader, HashMap, Resource};
+use ar_enemies::{BehaviorNode, MonsterAI};
+use bevy::prelude::{info, Res, ResMut};
+use ron::de::from_reader;
+
+/// The behavior trees that monsters can use, by key
+#[derive(Clone, Deserialize, Debug, Resource)]
+pub struct BehaviorTreeTemplates {
+    pub trees: HashMap<String, BehaviorNode>,
+}
+
+impl FromReader<File> for BehaviorTreeTemplates {
+    fn from_reader(reader: File) -> Result<Self, ron::error::SpannedError> {
+        from_reader(reader)
+    }
+}
+
+/// Removes the behavior trees of the monsters whose tree doesn't exist,
+/// the monster falls back to chasing its target
+pub fn validate_behavior_trees(
+    mut monster_templates: ResMut<MonsterTemplates>,
+    behavior_trees: Res<BehaviorTreeTemplates>,
+) {
+    for (name, template) in monster_templates.templates.iter_mut() {
+        let Some(MonsterAI::BehaviorTree { tree, .. }) = &template.ai else {
+            continue;
+        };
+        if !behavior_trees.trees.contains_key(tree) {
+            info!("Monster {}: unknown behavior tree {}", name, tree);
+            template.ai = None;
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{Resource, SpellTemplates};
-use ar_core::{AppState, CardType, CardsTemplates, RemainingCardsByType};
+use ar_core::{AppState, CardDraws, CardType, CardsTemplates, CurrentCards, RemainingCardsByType};
 use bevy::prelude::{info, Commands, NextState, Res, ResMut};
 
 #[derive(Resource)]
@@ -28,12 +28,22 @@ pub fn build_cards_by_type(cards_templates: Res<CardsTemplates>, mut commands: C
         powerup_cards: cards_by_type.powerup_cards.clone(),
         spell_cards: cards_by_type.spell_cards.clone(),
     };
+    // every card starts at level 0, it is level 1 once it is picked
+    let current_cards = CurrentCards {
+        cards: cards_templates
+            .cards
+            .keys()
+            .map(|key| (key.clone(), 0))
+            .collect(),
+    };
     commands.insert_resource(cards_by_type);
     commands.insert_resource(remaining_cards_by_type);
+    commands.insert_resource(current_cards);
+    commands.insert_resource(CardDraws::new(&cards_templates.draw_rules));
 }
 
 /// Remove spell cards that reference a spell that doesn't exist,
-/// it moves the app to the next stage after validating the spell cards
+/// it moves the app to the next stage, once the evolutions are validated too
 pub fn validate_spell_cards(
     mut cards_templates: ResMut<CardsTemplates>,
     mut cards_by_type: ResMut<CardsByType>,
@@ -0,0 +1,91 @@
+use crate::{Deserialize, File, FromReader, HashMap, Resource, SpellTemplates};
+use ar_core::{CardType, CardsTemplates, RemainingCardsByType};
+use bevy::prelude::{info, Res, ResMut};
+use ron::de::from_reader;
+
+/// Once the base card is at its max level and the catalyst card has been picked,
+/// the evolved card can be offered, its spell replaces the spell of the base card
+#[derive(Clone, Deserialize, Debug)]
+pub struct EvolutionTemplate {
+    pub base_card: String,     // A spell card
+    pub catalyst_card: String, // A buff card
+    pub evolved_card: String,  // A spell card, it is never offered outside of its evolution
+}
+
+#[derive(Clone, Deserialize, Debug, Resource)]
+pub struct EvolutionTemplates {
+    pub evolutions: HashMap<String, EvolutionTemplate>,
+}
+
+impl FromReader<File> for EvolutionTemplates {
+    fn from_reader(reader: File) -> Result<Self, ron::error::SpannedError> {
+        from_reader(reader)
+    }
+}
+
+impl EvolutionTemplates {
+    /// The evolution that offers the card
+    pub fn evolution_of(&self, evolved_card: &str) -> Option<&EvolutionTemplate> {
+        self.evolutions
+            .values()
+            .find(|evolution| evolution.evolved_card == evolved_card)
+    }
+}
+
+/// Remove evolutions that reference a card that doesn't exist or is of the wrong type,
+/// the evolved cards of the valid ones are removed from the remaining cards,
+/// it runs after validate_spell_cards so the cards of missing spells are already gone
+pub fn validate_evolutions(
+    mut evolution_templates: ResMut<EvolutionTemplates>,
+    mut remaining_cards: ResMut<RemainingCardsByType>,
+    cards_templates: Res<CardsTemplates>,
+    spell_templates: Res<SpellTemplates>,
+) {
+    let spell_of = |card: &str| {
+        cards_templates
+            .cards
+            .get(card)
+            .filter(|template| template.card_type == CardType::Spell)
+            .and_then(|template| template.spell.clone())
+            .filter(|spell| spell_templates.spells.contains_key(spell))
+    };
+
+    evolution_templates.evolutions.retain(|name, evolution| {
+        let Some(base_spell) = spell_of(&evolution.base_card) else {
+            info!(
+                "Evolution {}: invalid base card {}",
+                name, evolution.base_card
+            );
+            return false;
+        };
+        let Some(evolved_spell) = spell_of(&evolution.evolved_card) else {
+            info!(
+                "Evolution {}: invalid evolved card {}",
+                name, evolution.evolved_card
+            );
+            return false;
+        };
+        if base_spell == evolved_spell {
+            info!("Evolution {}: the evolved spell is the base spell", name);
+            return false;
+        }
+        let catalyst_is_buff = cards_templates
+            .cards
+            .get(&evolution.catalyst_card)
+            .is_some_and(|template| template.card_type == CardType::Buff);
+        if !catalyst_is_buff {
+            info!(
+                "Evolution {}: invalid catalyst card {}",
+                name, evolution.catalyst_card
+            );
+            return false;
+        }
+        true
+    });
+
+    for evolution in evolution_templates.evolutions.values() {
+        remaining_cards
+            .spell_cards
+            .retain(|card| *card != evolution.evolved_card);
+    }
+}
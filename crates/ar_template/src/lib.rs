@@ -1,4 +1,6 @@
+pub mod behaviortrees;
 pub mod cards;
+pub mod evolutions;
 pub mod items;
 pub mod monsters;
 pub mod spells;
@@ -13,9 +15,11 @@ use std::env;
 use std::path::PathBuf;
 
 use crate::{
+    behaviortrees::{validate_behavior_trees, BehaviorTreeTemplates},
     cards::{build_cards_by_type, validate_spell_cards},
+    evolutions::{validate_evolutions, EvolutionTemplates},
     items::{cache_templates_items_info, ItemTemplates},
-    monsters::{cache_templates_monsters_info, MonsterTemplates},
+    monsters::{cache_templates_monsters_info, validate_monster_ai, MonsterTemplates},
     spells::SpellTemplates,
 };
 
@@ -27,9 +31,11 @@ impl Plugin for TemplatePlugin {
             OnEnter(AppState::LoadingTemplates),
             (
                 load_templates,
+                (validate_monster_ai, validate_behavior_trees).chain(),
                 (cache_templates_monsters_info, cache_templates_items_info),
                 build_cards_by_type,
                 validate_spell_cards,
+                validate_evolutions,
             )
                 .chain()
                 .in_set(LoadingTemplatesSet),
@@ -42,11 +48,15 @@ pub fn load_templates(mut commands: Commands) {
     let mut monster_path = spell_path.clone();
     let mut item_path = spell_path.clone();
     let mut card_path = spell_path.clone();
+    let mut evolution_path = spell_path.clone();
+    let mut behavior_tree_path = spell_path.clone();
 
     spell_path.push("../ar_bin/assets/templates/spells.ron");
     monster_path.push("../ar_bin/assets/templates/monsters.ron");
     item_path.push("../ar_bin/assets/templates/items.ron");
     card_path.push("../ar_bin/assets/templates/cards.ron");
+    evolution_path.push("../ar_bin/assets/templates/evolutions.ron");
+    behavior_tree_path.push("../ar_bin/assets/templates/behaviortrees.ron");
 
     let spell_file = File::open(spell_path.clone())
         .unwrap_or_else(|_| panic!("failed to load {:?}", spell_path));
@@ -56,6 +66,10 @@ pub fn load_templates(mut commands: Commands) {
         File::open(item_path.clone()).unwrap_or_else(|_| panic!("failed to load {:?}", item_path));
     let card_file =
         File::open(card_path.clone()).unwrap_or_else(|_| panic!("failed to load {:?}", card_path));
+    let evolution_file = File::open(evolution_path.clone())
+        .unwrap_or_else(|_| panic!("failed to load {:?}", evolution_path));
+    let behavior_tree_file = File::open(behavior_tree_path.clone())
+        .unwrap_or_else(|_| panic!("failed to load {:?}", behavior_tree_path));
 
     let monstertemplate =
         MonsterTemplates::from_reader(monster_file).expect("failed to parse monsters.ron");
@@ -63,9 +77,15 @@ pub fn load_templates(mut commands: Commands) {
         SpellTemplates::from_reader(spell_file).expect("failed to parse spells.ron");
     let itemtemplate = ItemTemplates::from_reader(item_file).expect("failed to parse items.ron");
     let cardtemplate = CardsTemplates::from_reader(card_file).expect("failed to parse cards.ron");
+    let evolutiontemplate =
+        EvolutionTemplates::from_reader(evolution_file).expect("failed to parse evolutions.ron");
+    let behaviortreetemplate = BehaviorTreeTemplates::from_reader(behavior_tree_file)
+        .expect("failed to parse behaviortrees.ron");
 
     commands.insert_resource(monstertemplate);
     commands.insert_resource(spelltemplate);
     commands.insert_resource(itemtemplate);
     commands.insert_resource(cardtemplate);
+    commands.insert_resource(evolutiontemplate);
+    commands.insert_resource(behaviortreetemplate);
 }
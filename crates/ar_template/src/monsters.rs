@@ -1,8 +1,12 @@
-use crate::{Commands, Deserialize, File, FromReader, HashMap, Res, Resource};
-use ar_core::{MonsterLayoutType, RewardType, WeaponType};
+use crate::{Commands, Deserialize, File, FromReader, HashMap, Res, ResMut, Resource};
+use ar_core::{
+    Armor, MonsterLayoutType, Resistances, RewardType, ShieldTemplate, StatusEffectTemplate,
+    WeaponType,
+};
 use ron::de::from_reader;
 
-use ar_enemies::{MonsterAI, QualityMonster};
+use ar_enemies::{MonsterAI, QualityMonster, StateMachineTemplate};
+use bevy::prelude::info;
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct MonsterTemplate {
@@ -20,6 +24,14 @@ pub struct MonsterTemplate {
     pub rewards: Option<Vec<RewardType>>,
     // The AI of the monster, if none then it just stays still
     pub ai: Option<MonsterAI>,
+    // Reduces the damage the monster takes
+    pub armor: Option<Armor>,
+    // Absorbs the damage the monster takes, regenerating over time
+    pub shield: Option<ShieldTemplate>,
+    // The fraction of each damage type that the monster ignores
+    pub resistances: Option<Resistances>,
+    // The status effect applied to the player when the monster hits
+    pub attack_effect: Option<StatusEffectTemplate>,
     // The weapons that the monster can use, if no weapon then it just walks
     // towards the player
     pub weapons: Option<Vec<WeaponType>>,
@@ -118,3 +130,27 @@ pub fn cache_templates_monsters_info(
         difficulty_4,
     });
 }
+
+/// Removes the state machines whose initial state or transitions lead to a state
+/// that doesn't exist, the monster falls back to chasing its target
+pub fn validate_monster_ai(mut monster_templates: ResMut<MonsterTemplates>) {
+    for (name, template) in monster_templates.templates.iter_mut() {
+        let Some(MonsterAI::StateMachine(machine)) = &template.ai else {
+            continue;
+        };
+        if let Some(state) = missing_state(machine) {
+            info!("Monster {}: unknown AI state {}", name, state);
+            template.ai = None;
+        }
+    }
+}
+
+fn missing_state(machine: &StateMachineTemplate) -> Option<&String> {
+    let transitions = machine
+        .states
+        .values()
+        .flat_map(|state| state.transitions.iter().map(|t| &t.to));
+    std::iter::once(&machine.initial)
+        .chain(transitions)
+        .find(|state| !machine.states.contains_key(*state))
+}
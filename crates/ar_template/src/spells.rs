@@ -1,7 +1,8 @@
 use crate::{Deserialize, File, FromReader, HashMap, Resource};
 use ar_core::{
-    SpellAOE, SpellBuffType, SpellProjectileExplosiveType, SpellProjectileType, SpellSummonType,
-    SpellSwingType, SpellType,
+    SpellAOE, SpellAuraType, SpellBeamType, SpellBuffType, SpellChainType, SpellLevelUp,
+    SpellOrbitType, SpellProjectileExplosiveType, SpellProjectileType, SpellSummonType,
+    SpellSwingType, SpellTrapType, SpellType,
 };
 use ron::de::from_reader;
 
@@ -17,6 +18,13 @@ pub struct SpellTemplate {
     pub buff_type_struct: Option<SpellBuffType>,
     pub aoe_type_struct: Option<SpellAOE>,
     pub explosive_projectile_struct: Option<SpellProjectileExplosiveType>,
+    pub chain_type_struct: Option<SpellChainType>,
+    pub beam_type_struct: Option<SpellBeamType>,
+    pub orbit_type_struct: Option<SpellOrbitType>,
+    pub trap_type_struct: Option<SpellTrapType>,
+    pub aura_type_struct: Option<SpellAuraType>,
+    // If it is of type None then picking the spell's card again doesn't change the spell
+    pub level_up: Option<SpellLevelUp>,
 }
 
 #[derive(Clone, Deserialize, Debug, Resource)]
@@ -0,0 +1,106 @@
+use crate::displaycards::CardsSprite;
+use crate::{FontAssets, UiSet};
+use ar_core::{ActiveBuffs, AppState, PlayerMarker};
+use bevy::prelude::*;
+
+pub struct BuffIconsPlugin;
+
+impl Plugin for BuffIconsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InBattle),
+            set_display_buff_icons.in_set(UiSet),
+        )
+        .add_systems(FixedUpdate, update_buff_icons.in_set(UiSet));
+    }
+}
+
+/// A marker for the node that holds the icons of the active buffs
+#[derive(Component)]
+struct BuffIconsContainer;
+
+/// The text showing the remaining time of the buff at the index
+#[derive(Component)]
+struct BuffIconText(usize);
+
+fn set_display_buff_icons(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(4.0),
+            position_type: PositionType::Absolute,
+            top: Val::Px(24.0),
+            left: Val::Px(4.0),
+            ..default()
+        })
+        .insert(Name::new("buff_icons_container"))
+        .insert(BuffIconsContainer);
+}
+
+fn buff_text(remaining: f32, stacks: u8) -> String {
+    if stacks > 1 {
+        format!("{:.1}s x{}", remaining, stacks)
+    } else {
+        format!("{:.1}s", remaining)
+    }
+}
+
+/// Rebuilds the icons when a buff is added or removed,
+/// otherwise only updates the remaining time of each buff
+fn update_buff_icons(
+    mut commands: Commands,
+    container: Single<Entity, With<BuffIconsContainer>>,
+    buffs: Single<&ActiveBuffs, With<PlayerMarker>>,
+    cards_sprite: Res<CardsSprite>,
+    fonts: Res<FontAssets>,
+    mut texts: Query<(&BuffIconText, &mut Text)>,
+    mut shown: Local<Vec<String>>,
+) {
+    let names_changed =
+        shown.len() != buffs.0.len() || shown.iter().zip(buffs.0.iter()).any(|(a, b)| *a != b.name);
+
+    if !names_changed {
+        for (index, mut text) in texts.iter_mut() {
+            if let Some(buff) = buffs.0.get(index.0) {
+                text.0 = buff_text(buff.timer.remaining_secs(), buff.stacks);
+            }
+        }
+        return;
+    }
+
+    *shown = buffs.0.iter().map(|b| b.name.clone()).collect();
+    let color: Color = Color::srgba_u8(15, 56, 15, 255);
+    commands
+        .entity(*container)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for (i, buff) in buffs.0.iter().enumerate() {
+                parent
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|icon| {
+                        if let Some(image) = cards_sprite.cards_sprites.get(buff.sprite.as_str()) {
+                            icon.spawn(ImageNode::new(image.clone())).insert(Node {
+                                width: Val::Px(24.0),
+                                height: Val::Px(24.0),
+                                ..default()
+                            });
+                        }
+                        icon.spawn(Text::new(buff_text(
+                            buff.timer.remaining_secs(),
+                            buff.stacks,
+                        )))
+                        .insert(TextFont {
+                            font: fonts.ui_font.clone(),
+                            font_size: 8.0,
+                            ..default()
+                        })
+                        .insert(TextColor(color))
+                        .insert(BuffIconText(i));
+                    });
+            }
+        });
+}
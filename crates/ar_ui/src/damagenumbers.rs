@@ -1,4 +1,6 @@
-use crate::{DisplayDamageEvent, FontAssets, LifeTime, PlayerMarker, PlayerMinusHpEvent, UiSet};
+use crate::{
+    DamageType, DisplayDamageEvent, FontAssets, LifeTime, PlayerMarker, PlayerMinusHpEvent, UiSet,
+};
 use bevy::prelude::*;
 pub struct DamageNumbersPlugin;
 
@@ -51,16 +53,21 @@ fn display_damage_numbers(
         return;
     }
     let font = fonts.damage_font.clone();
-    let color: Color = Color::srgba_u8(15, 56, 15, 255);
 
     for ev in damage.read() {
         if let Ok(transform) = monster_pos.get(ev.target) {
-            let displayed_number = ev.damage.to_string();
+            let color = damage_type_color(ev.damage_type);
+            // critical hits are shown bigger and with an exclamation mark
+            let (displayed_number, font_size) = if ev.crit {
+                (format!("{}!", ev.damage), 24.0)
+            } else {
+                (ev.damage.to_string(), 18.0)
+            };
             commands
                 .spawn(Text2d::new(displayed_number))
                 .insert(TextFont {
                     font: font.clone(),
-                    font_size: 18.0,
+                    font_size,
                     ..default()
                 })
                 .insert(TextColor(color))
@@ -71,3 +78,12 @@ fn display_damage_numbers(
         }
     }
 }
+
+fn damage_type_color(damage_type: DamageType) -> Color {
+    match damage_type {
+        DamageType::Physical => Color::srgba_u8(15, 56, 15, 255),
+        DamageType::Fire => Color::srgba_u8(200, 70, 20, 255),
+        DamageType::Ice => Color::srgba_u8(40, 110, 200, 255),
+        DamageType::Poison => Color::srgba_u8(90, 150, 20, 255),
+    }
+}
@@ -1,10 +1,20 @@
-use ar_core::{AppState, CardsTemplates, ChooseACard, UiSet};
+use crate::FontAssets;
+use ar_core::{
+    AppState, Armor, CardDraws, CardType, CardsTemplate, CardsTemplates, ChooseACard, CurrentCards,
+    Damage, PermanentDebuff, PlayerMarker, PlayerStats, PowerUp, Shield, Stat, UiSet,
+};
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy_asset_loader::prelude::*;
 
 pub struct DisplayCardsPlugin;
 
+/// The background of the cards
+const CARD_BACKGROUND_COLOR: Color = Color::srgba_u8(155, 188, 15, 255);
+/// The background and the tint of the template of cursed cards
+const CURSED_BACKGROUND_COLOR: Color = Color::srgba_u8(120, 30, 60, 255);
+const CURSED_TEMPLATE_TINT: Color = Color::srgba_u8(220, 120, 160, 255);
+
 #[derive(Component, Debug)]
 pub struct CardUiMarker;
 
@@ -14,11 +24,21 @@ pub struct CardArtMarker;
 #[derive(Component, Debug)]
 pub struct CardTemplateMarker;
 
+/// The text of the card, with the stat delta of its power up or the level of its spell
+#[derive(Component, Debug)]
+pub struct CardTextMarker;
+
+/// The keys and the uses left of the card actions, shown under the cards
+#[derive(Component, Debug)]
+pub struct CardActionsTextMarker;
+
 #[derive(Resource, Debug)]
 pub struct CardUiHelper {
     pub card_ui_id: [Entity; 3],
     pub card_template_id: [Entity; 3],
     pub card_art_id: [Entity; 3],
+    pub card_text_id: [Entity; 3],
+    pub card_actions_text_id: Entity,
 }
 
 #[derive(Debug, AssetCollection, Resource)]
@@ -57,7 +77,7 @@ impl Plugin for DisplayCardsPlugin {
 /// When cards are spawned another system modifies these entities,
 /// revealing them and updating the art of the card, when the resource
 /// `ChooseACard` is empty it goes back to being hidden
-fn cards_ui_set_up(mut commands: Commands, cards_sprite: Res<CardsSprite>) {
+fn cards_ui_set_up(mut commands: Commands, cards_sprite: Res<CardsSprite>, fonts: Res<FontAssets>) {
     let template_uncommon = cards_sprite
         .cards_sprites
         .get("card_uncommon")
@@ -78,7 +98,7 @@ fn cards_ui_set_up(mut commands: Commands, cards_sprite: Res<CardsSprite>) {
         .get("health")
         .expect("health art not loaded");
 
-    let background_color: Color = Color::srgba_u8(155, 188, 15, 255);
+    let background_color = CARD_BACKGROUND_COLOR;
 
     let container = Node {
         flex_direction: FlexDirection::Row,
@@ -122,6 +142,20 @@ fn cards_ui_set_up(mut commands: Commands, cards_sprite: Res<CardsSprite>) {
         ..default()
     };
 
+    let card_text_node = Node {
+        position_type: PositionType::Absolute,
+        left: Val::Percent(6.0),
+        right: Val::Percent(6.0),
+        top: Val::Percent(66.0),
+        ..default()
+    };
+    let card_text_font = TextFont {
+        font: fonts.ui_font.clone(),
+        font_size: 8.0,
+        ..default()
+    };
+    let card_text_color = TextColor(Color::srgba_u8(15, 56, 15, 255));
+
     let parent = commands
         .spawn(container)
         .insert(Name::new("cards_ui_container"))
@@ -187,6 +221,34 @@ fn cards_ui_set_up(mut commands: Commands, cards_sprite: Res<CardsSprite>) {
         .insert(CardArtMarker)
         .id();
 
+    let [card_text1, card_text2, card_text3] = [1, 2, 3].map(|i| {
+        commands
+            .spawn(card_text_node.clone())
+            .insert(Text::default())
+            .insert(TextLayout::new_with_justify(JustifyText::Center))
+            .insert(card_text_font.clone())
+            .insert(card_text_color)
+            .insert(Name::new(format!("card_text{}", i)))
+            .insert(CardTextMarker)
+            .id()
+    });
+
+    let card_actions_text = commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            bottom: Val::Percent(3.0),
+            ..default()
+        })
+        .insert(Text::default())
+        .insert(TextLayout::new_with_justify(JustifyText::Center))
+        .insert(card_text_font.clone())
+        .insert(TextColor(background_color))
+        .insert(Visibility::Hidden)
+        .insert(Name::new("card_actions_text"))
+        .insert(CardActionsTextMarker)
+        .id();
+
     commands.entity(parent).add_children(&[card1, card2, card3]);
 
     commands.entity(card1).add_children(&[template1]);
@@ -197,10 +259,16 @@ fn cards_ui_set_up(mut commands: Commands, cards_sprite: Res<CardsSprite>) {
     commands.entity(template2).add_children(&[card_art2]);
     commands.entity(template3).add_children(&[card_art3]);
 
+    commands.entity(template1).add_children(&[card_text1]);
+    commands.entity(template2).add_children(&[card_text2]);
+    commands.entity(template3).add_children(&[card_text3]);
+
     let card_ui_helper = CardUiHelper {
         card_ui_id: [card1, card2, card3],
         card_template_id: [template1, template2, template3],
         card_art_id: [card_art1, card_art2, card_art3],
+        card_text_id: [card_text1, card_text2, card_text3],
+        card_actions_text_id: card_actions_text,
     };
 
     commands.insert_resource(card_ui_helper);
@@ -208,9 +276,9 @@ fn cards_ui_set_up(mut commands: Commands, cards_sprite: Res<CardsSprite>) {
 
 /// Display/Hide available cards
 /// Runs when the `AvailableCards` resource is updated
-#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn display_hide_available_cards(
-    mut card_ui_query: Query<&mut Visibility, With<CardUiMarker>>,
+    mut card_ui_query: Query<(&mut Visibility, &mut BackgroundColor), With<CardUiMarker>>,
     mut card_template_query: Query<
         (&mut Visibility, &mut ImageNode),
         (
@@ -223,10 +291,23 @@ fn display_hide_available_cards(
         (&mut Visibility, &mut ImageNode),
         (With<CardArtMarker>, Without<CardUiMarker>),
     >,
+    mut card_actions_query: Query<
+        &mut Visibility,
+        (
+            With<CardActionsTextMarker>,
+            Without<CardUiMarker>,
+            Without<CardTemplateMarker>,
+            Without<CardArtMarker>,
+        ),
+    >,
     cards_helper: Res<CardUiHelper>,
     cards_sprites: Res<CardsSprite>,
     chosen_cards: Res<ChooseACard>,
     cards_res: Res<CardsTemplates>,
+    current_cards: Res<CurrentCards>,
+    card_draws: Res<CardDraws>,
+    player: Query<(&PlayerStats, &Armor, &Shield, &Damage), With<PlayerMarker>>,
+    mut writer: TextUiWriter,
 ) {
     let mut available_cards = 0;
     if !chosen_cards.cards.is_empty() {
@@ -240,7 +321,7 @@ fn display_hide_available_cards(
     let available_cards = available_cards;
 
     if available_cards == 0 {
-        for mut visibility in &mut card_ui_query {
+        for (mut visibility, _) in &mut card_ui_query {
             *visibility = Visibility::Hidden;
         }
         for (mut visibility, _) in &mut card_art_query {
@@ -249,33 +330,69 @@ fn display_hide_available_cards(
         for (mut visibility, _) in &mut card_template_query {
             *visibility = Visibility::Hidden;
         }
+        if let Ok(mut visibility) = card_actions_query.get_mut(cards_helper.card_actions_text_id) {
+            *visibility = Visibility::Hidden;
+        }
     } else if available_cards <= 3 {
-        for i in 0..available_cards as usize {
-            let mut card_ui_visibility = card_ui_query
+        if let Ok(mut visibility) = card_actions_query.get_mut(cards_helper.card_actions_text_id) {
+            *visibility = Visibility::Visible;
+        }
+        *writer.text(cards_helper.card_actions_text_id, 0) = format!(
+            "[R] Reroll ({})   [X] Skip ({})   [B]+[1-3] Banish ({})",
+            card_draws.rerolls, card_draws.skips, card_draws.banishes
+        );
+        // a banished card without a replacement leaves its slot empty
+        for i in 0..3 {
+            let (mut card_ui_visibility, mut card_ui_background) = card_ui_query
                 .get_mut(cards_helper.card_ui_id[i])
                 .expect("Cards Helper");
-            *card_ui_visibility = Visibility::Visible;
-
             let (mut card_template_visibility, mut card_template_image) = card_template_query
                 .get_mut(cards_helper.card_template_id[i])
                 .unwrap();
-
+            let (mut card_art_visibility, mut card_art_image) =
+                card_art_query.get_mut(cards_helper.card_art_id[i]).unwrap();
+            let Some(card_name) = chosen_cards.cards[0][i].clone() else {
+                *card_ui_visibility = Visibility::Hidden;
+                *card_template_visibility = Visibility::Hidden;
+                *card_art_visibility = Visibility::Hidden;
+                continue;
+            };
+            *card_ui_visibility = Visibility::Visible;
             *card_template_visibility = Visibility::Visible;
-            let card_name = chosen_cards.cards[0][i].clone().expect("Chosen Cards OOB");
             let card_template_res = cards_res.cards.get(&card_name).expect("Card doesn't exist");
             let card_sprite = card_template_res.sprite.clone();
             let card_rarity = "card_uncommon".to_string();
+            // cursed cards get a distinct frame
+            let cursed = card_template_res.debuff.is_some();
+            *card_ui_background = BackgroundColor(if cursed {
+                CURSED_BACKGROUND_COLOR
+            } else {
+                CARD_BACKGROUND_COLOR
+            });
             *card_template_image = ImageNode {
                 image: cards_sprites
                     .cards_sprites
                     .get(card_rarity.as_str())
                     .unwrap_or_else(|| panic!("Card Template not found {:?}", card_rarity))
                     .clone(),
+                color: if cursed {
+                    CURSED_TEMPLATE_TINT
+                } else {
+                    Color::WHITE
+                },
                 ..Default::default()
             };
 
-            let (mut card_art_visibility, mut card_art_image) =
-                card_art_query.get_mut(cards_helper.card_art_id[i]).unwrap();
+            let level = current_cards
+                .cards
+                .get(&card_name)
+                .copied()
+                .unwrap_or_default();
+            *writer.text(cards_helper.card_text_id[i], 0) = match player.get_single() {
+                Ok(player) => card_text(card_template_res, level, player),
+                Err(_) => card_template_res.name.clone(),
+            };
+
             *card_art_visibility = Visibility::Visible;
 
             *card_art_image = ImageNode {
@@ -291,3 +408,105 @@ fn display_hide_available_cards(
         info!("Available Cards: {:?}", available_cards);
     }
 }
+
+/// The name of the card followed by what picking it does:
+/// the stat with its current and resulting value for power ups,
+/// the description or the next level for spells,
+/// and the drawback of cursed cards
+fn card_text(
+    card: &CardsTemplate,
+    level: u8,
+    (stats, armor, shield, damage): (&PlayerStats, &Armor, &Shield, &Damage),
+) -> String {
+    let detail = match (&card.card_type, &card.upgrade) {
+        (CardType::Buff, Some(power_up)) => match power_up.stat_modifier() {
+            Some(modifier) => {
+                let mut upgraded = stats.clone();
+                upgraded.add_modifier(modifier.clone());
+                format!(
+                    "{}: {} -> {}",
+                    stat_name(modifier.stat),
+                    format_stat(stats.get(modifier.stat)),
+                    format_stat(upgraded.get(modifier.stat)),
+                )
+            }
+            None => match power_up {
+                PowerUp::AttackUp(x) => {
+                    format!("Damage: +{} -> +{}", damage.0, damage.0 + *x as usize)
+                }
+                PowerUp::ShieldUp(x) => format!(
+                    "Shield: {} -> {}",
+                    format_stat(shield.max),
+                    format_stat(shield.max + *x as f32)
+                ),
+                PowerUp::ArmorUp(x) => {
+                    format!("Armor: {} -> {}", armor.flat, armor.flat + *x as usize)
+                }
+                _ => card.description.clone(),
+            },
+        },
+        (CardType::Spell, _) if level > 0 => format!("Level {} -> {}", level, level + 1),
+        _ => card.description.clone(),
+    };
+    match &card.debuff {
+        Some(debuff) => format!(
+            "{}\n{}\nCurse: {}",
+            card.name,
+            detail,
+            debuff_text(debuff, stats, damage)
+        ),
+        None => format!("{}\n{}", card.name, detail),
+    }
+}
+
+/// The stat lowered by the debuff with its current and resulting value
+fn debuff_text(debuff: &PermanentDebuff, stats: &PlayerStats, damage: &Damage) -> String {
+    match debuff.stat_modifier() {
+        Some(modifier) => {
+            let mut cursed = stats.clone();
+            cursed.add_modifier(modifier.clone());
+            format!(
+                "{}: {} -> {}",
+                stat_name(modifier.stat),
+                format_stat(stats.get(modifier.stat)),
+                format_stat(cursed.get(modifier.stat)),
+            )
+        }
+        None => match debuff {
+            PermanentDebuff::AttackDown(x) => format!(
+                "Damage: +{} -> +{}",
+                damage.0,
+                damage.0.saturating_sub(*x as usize)
+            ),
+            _ => format!("{:?}", debuff),
+        },
+    }
+}
+
+fn stat_name(stat: Stat) -> &'static str {
+    match stat {
+        Stat::MoveSpeed => "Speed",
+        Stat::Might => "Might",
+        Stat::Cooldown => "Cooldown",
+        Stat::Area => "Area",
+        Stat::Duration => "Duration",
+        Stat::Amount => "Amount",
+        Stat::Luck => "Luck",
+        Stat::Growth => "Growth",
+        Stat::Greed => "Greed",
+        Stat::Magnet => "Magnet",
+        Stat::MaxHealth => "Max HP",
+        Stat::MaxStamina => "Max Stamina",
+        Stat::CritChance => "Crit Chance",
+        Stat::CritDamage => "Crit Damage",
+    }
+}
+
+/// Whole numbers are shown without decimals, multipliers with two
+fn format_stat(value: f32) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
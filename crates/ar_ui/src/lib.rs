@@ -1,13 +1,15 @@
 // Crate for the UI and text on the floor
 
+pub mod buffs;
 pub mod damagenumbers;
 pub mod displaycards;
 
+use crate::buffs::BuffIconsPlugin;
 use crate::damagenumbers::DamageNumbersPlugin;
 use crate::displaycards::DisplayCardsPlugin;
 use ar_core::{
-    AppState, CurrentStamina, DisplayDamageEvent, Health, LifeTime, MaxHealth, MaxStamina,
-    PlayerMarker, PlayerMinusHpEvent, UiMarker, UiSet,
+    AppState, CurrentStamina, DamageType, DisplayDamageEvent, Health, LifeTime, MaxHealth,
+    MaxStamina, PlayerMarker, PlayerMinusHpEvent, Shield, UiMarker, UiSet,
 };
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
@@ -30,17 +32,27 @@ struct PlayerHealthText;
 #[derive(Component)]
 struct PlayerStaminaText;
 
+/// A marker for the background of the shield bar, hidden while the player has no shield
+#[derive(Component)]
+struct PlayerShieldBar;
+
+/// A marker for the filled part of the shield bar
+#[derive(Component)]
+struct PlayerShieldFill;
+
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(DamageNumbersPlugin)
             .add_plugins(DisplayCardsPlugin)
+            .add_plugins(BuffIconsPlugin)
             .add_systems(
                 OnEnter(AppState::InBattle),
                 (
                     set_display_player_health.in_set(UiSet),
                     set_display_player_stamina.in_set(UiSet),
+                    set_display_player_shield.in_set(UiSet),
                 ),
             )
             .add_systems(
@@ -48,6 +60,7 @@ impl Plugin for UiPlugin {
                 (
                     update_health_displayer.in_set(UiSet),
                     update_stamina_displayer.in_set(UiSet),
+                    update_shield_displayer.in_set(UiSet),
                 ),
             );
     }
@@ -99,7 +112,7 @@ fn set_display_player_stamina(
     stamina: Query<(&CurrentStamina, &MaxStamina), With<PlayerMarker>>,
 ) {
     let (stamina, max_stamina) = stamina.single();
-    let text = format!("Stamina: {} / {}", stamina.0, max_stamina.0);
+    let text = format!("Stamina: {:.0} / {:.0}", stamina.0, max_stamina.0);
     let font = fonts.ui_font.clone();
     let color: Color = Color::srgba_u8(15, 56, 15, 255);
 
@@ -129,5 +142,44 @@ fn update_stamina_displayer(
     mut writer: TextUiWriter,
 ) {
     let (stamina, max_stamina) = stamina_value.single();
-    *writer.text(*stamina_text, 0) = format!("Stamina: {} / {}", stamina.0, max_stamina.0);
+    *writer.text(*stamina_text, 0) = format!("Stamina: {:.0} / {:.0}", stamina.0, max_stamina.0);
+}
+
+fn set_display_player_shield(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(18.0),
+            right: Val::Percent(40.0),
+            width: Val::Px(160.0),
+            height: Val::Px(6.0),
+            ..default()
+        })
+        .insert(BackgroundColor(Color::srgba_u8(15, 56, 15, 255)))
+        .insert(Visibility::Hidden)
+        .insert(UiMarker)
+        .insert(PlayerShieldBar)
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                })
+                .insert(BackgroundColor(Color::srgba_u8(120, 180, 255, 255)))
+                .insert(PlayerShieldFill);
+        });
+}
+
+fn update_shield_displayer(
+    mut shield_bar: Single<&mut Visibility, With<PlayerShieldBar>>,
+    mut shield_fill: Single<&mut Node, With<PlayerShieldFill>>,
+    shield: Single<&Shield, With<PlayerMarker>>,
+) {
+    if shield.max <= 0.0 {
+        **shield_bar = Visibility::Hidden;
+        return;
+    }
+    **shield_bar = Visibility::Inherited;
+    shield_fill.width = Val::Percent((shield.current / shield.max * 100.0).clamp(0.0, 100.0));
 }